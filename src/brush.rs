@@ -0,0 +1,566 @@
+use bevy::input::mouse::{AccumulatedMouseScroll, MouseScrollUnit};
+use bevy::prelude::*;
+
+use crate::VOXEL_SIZE;
+use crate::brush_shape::{BrushShape, Footprint};
+use crate::material::Material;
+use crate::voxel_map::VoxelMap;
+
+// --- 笔刷插件：处理挖掘/填补 ---
+pub struct BrushPlugin;
+
+impl Plugin for BrushPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BrushSettings>()
+            .init_resource::<BrushStroke>()
+            .add_systems(
+                Update,
+                (adjust_brush, apply_brush, apply_shape_brush, draw_brush).chain(),
+            );
+    }
+}
+
+// 笔刷模式：挖/填之外，还有几种只修改现有密度的笔刷
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum BrushMode {
+    #[default]
+    Sculpt, // 左键挖，右键填
+    Smooth,  // 往周围的平均值靠拢，模糊掉锯齿
+    Flatten, // 往下笔位置的密度靠拢
+    Erode,   // 只削不补，凸出的尖角最先被磨圆
+}
+
+impl BrushMode {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Sculpt => Self::Smooth,
+            Self::Smooth => Self::Flatten,
+            Self::Flatten => Self::Erode,
+            Self::Erode => Self::Sculpt,
+        }
+    }
+}
+
+// 笔刷的衰减曲线：离中心越远，改变量越小
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum BrushFalloff {
+    Constant, // 整个圆内一样（原来的行为）
+    Linear,
+    #[default]
+    Smoothstep,
+    Gaussian,
+}
+
+impl BrushFalloff {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Constant => Self::Linear,
+            Self::Linear => Self::Smoothstep,
+            Self::Smoothstep => Self::Gaussian,
+            Self::Gaussian => Self::Constant,
+        }
+    }
+
+    // t = 距离 / 半径，返回 0..1 的权重，圆外为 0
+    pub fn weight(self, t: f32) -> f32 {
+        if !(0.0..=1.0).contains(&t) {
+            return 0.0;
+        }
+        match self {
+            Self::Constant => 1.0,
+            Self::Linear => 1.0 - t,
+            Self::Smoothstep => {
+                let s = 1.0 - t;
+                s * s * (3.0 - 2.0 * s)
+            }
+            Self::Gaussian => (-4.5 * t * t).exp(), // 边缘处约为 0.01
+        }
+    }
+}
+
+// 笔刷参数，运行时可以用滚轮和按键调整
+#[derive(Resource, Clone, Debug)]
+pub struct BrushSettings {
+    pub radius: f32,           // 影响半径（格子数）
+    pub strength: f32,         // 每秒改变的密度
+    pub material: Material,    // 填充用的材质
+    pub falloff: BrushFalloff, // 衰减曲线（只用于圆形）
+    pub shape: BrushShape,
+    pub angle: f32, // 长方形的旋转角度（弧度）
+    pub mode: BrushMode,
+}
+
+impl Default for BrushSettings {
+    fn default() -> Self {
+        Self {
+            radius: 4.0,
+            strength: 6.0, // 相当于原来 60 帧下每帧 0.1
+            material: Material::Dirt,
+            falloff: BrushFalloff::default(),
+            shape: BrushShape::default(),
+            angle: 0.0,
+            mode: BrushMode::default(),
+        }
+    }
+}
+
+impl BrushSettings {
+    pub const MIN_RADIUS: f32 = 1.0;
+    pub const MAX_RADIUS: f32 = 32.0;
+    pub const MIN_STRENGTH: f32 = 0.5;
+    pub const MAX_STRENGTH: f32 = 60.0;
+
+    pub const ROTATE_STEP: f32 = std::f32::consts::PI / 12.0; // 15°
+
+    // 实际生效的形状：平滑、压平和侵蚀总是用圆形
+    pub fn active_shape(&self) -> BrushShape {
+        match self.mode {
+            BrushMode::Sculpt => self.shape,
+            _ => BrushShape::Circle,
+        }
+    }
+
+    // 以 center 为中心盖一个章；胶囊和多边形不跟着鼠标盖章，由 apply_shape_brush 单独提交
+    pub fn stamp(&self, map: &mut VoxelMap, center: Vec2, amount: f32, digging: bool) {
+        match self.footprint(center) {
+            Some(footprint) => self.carve(map, &footprint, amount, digging),
+            None if self.active_shape() == BrushShape::Circle => {
+                self.stamp_circle(map, center, amount, digging)
+            }
+            None => {}
+        }
+    }
+
+    // 方形和长方形在 center 处的形状
+    pub fn footprint(&self, center: Vec2) -> Option<Footprint> {
+        let half = self.radius * VOXEL_SIZE;
+        match self.active_shape() {
+            BrushShape::Square => Some(Footprint::Box {
+                center,
+                half_size: Vec2::splat(half),
+                angle: 0.0,
+            }),
+            BrushShape::Rectangle => Some(Footprint::Box {
+                center,
+                half_size: Vec2::new(half, half * 0.5),
+                angle: self.angle,
+            }),
+            _ => None,
+        }
+    }
+
+    // 按覆盖率把形状刻进密度场：挖的时候密度不低于 1 - 覆盖率，填的时候不高于覆盖率，
+    // 按住不动也不会把边缘越磨越宽
+    pub fn carve(&self, map: &mut VoxelMap, footprint: &Footprint, amount: f32, digging: bool) {
+        if !footprint.is_valid() {
+            return;
+        }
+        let (min, max) = footprint.bounds();
+        let margin = Vec2::splat(VOXEL_SIZE);
+        let (min_x, min_y) = map.world_to_grid(min - margin);
+        let (max_x, max_y) = map.world_to_grid(max + margin);
+
+        for y in min_y..=max_y + 1 {
+            for x in min_x..=max_x + 1 {
+                let coverage = footprint.coverage(map.grid_to_world(x, y));
+                if coverage <= 0.0 {
+                    continue;
+                }
+                if digging {
+                    map.dig_density_to(x, y, amount, 1.0 - coverage);
+                } else {
+                    map.fill_density_to(x, y, amount, coverage, self.material);
+                }
+            }
+        }
+    }
+
+    // 平滑/压平/侵蚀：按衰减曲线把圆内的密度往目标值拉，已有的材质不变
+    pub fn filter(&self, map: &mut VoxelMap, center: Vec2, amount: f32, target: f32) {
+        let radius = self.radius * VOXEL_SIZE;
+        let (min_x, min_y) = map.world_to_grid(center - Vec2::splat(radius));
+        let (max_x, max_y) = map.world_to_grid(center + Vec2::splat(radius));
+
+        // 先按修改前的密度算出所有新值再统一写回，结果和遍历顺序无关
+        let mut changes = Vec::new();
+        for y in min_y..=max_y + 1 {
+            for x in min_x..=max_x + 1 {
+                let dist = map.grid_to_world(x, y).distance(center);
+                let weight = self.falloff.weight(dist / radius);
+                if weight <= 0.0 {
+                    continue;
+                }
+                let current = map.get_density(x, y);
+                let goal = match self.mode {
+                    BrushMode::Sculpt => continue,
+                    BrushMode::Smooth => neighbour_average(map, x, y),
+                    BrushMode::Flatten => target,
+                    BrushMode::Erode => neighbour_average(map, x, y).min(current),
+                };
+                let value = current + (goal - current) * (amount * weight).min(1.0);
+                if value != current {
+                    changes.push((x, y, current, value));
+                }
+            }
+        }
+
+        // 降低密度和挖掘一样受硬度影响，但不算挖矿；从空气变成墙的格子用周围最实的格子的材质
+        for (x, y, current, value) in changes {
+            if value < current {
+                map.erode_density_to(x, y, current - value, value);
+            } else {
+                let material = densest_neighbour(map, x, y);
+                map.fill_density_to(x, y, value - current, value, material);
+            }
+        }
+    }
+
+    // 圆形：每个格点按到圆心的精确距离计算衰减
+    fn stamp_circle(&self, map: &mut VoxelMap, center: Vec2, amount: f32, digging: bool) {
+        let radius = self.radius * VOXEL_SIZE;
+        let (min_x, min_y) = map.world_to_grid(center - Vec2::splat(radius));
+        let (max_x, max_y) = map.world_to_grid(center + Vec2::splat(radius));
+
+        for y in min_y..=max_y + 1 {
+            for x in min_x..=max_x + 1 {
+                let dist = map.grid_to_world(x, y).distance(center);
+                let weight = self.falloff.weight(dist / radius);
+                if weight <= 0.0 {
+                    continue;
+                }
+                // 挖掘会受材质硬度影响
+                if digging {
+                    map.dig_density(x, y, amount * weight);
+                } else {
+                    map.fill_density(x, y, amount * weight, self.material);
+                }
+            }
+        }
+    }
+}
+
+// 3x3 邻域（含自己）的平均密度
+fn neighbour_average(map: &VoxelMap, x: i32, y: i32) -> f32 {
+    let mut sum = 0.0;
+    for dy in -1..=1 {
+        for dx in -1..=1 {
+            sum += map.get_density(x + dx, y + dy);
+        }
+    }
+    sum / 9.0
+}
+
+// 3x3 邻域里密度最大的格子的材质
+fn densest_neighbour(map: &VoxelMap, x: i32, y: i32) -> Material {
+    let mut best = (x, y);
+    for dy in -1..=1 {
+        for dx in -1..=1 {
+            let (nx, ny) = (x + dx, y + dy);
+            if map.get_density(nx, ny) > map.get_density(best.0, best.1) {
+                best = (nx, ny);
+            }
+        }
+    }
+    map.get_material(best.0, best.1)
+}
+
+// 当前这一笔的状态：记住上一帧盖章的位置，快速移动时沿线段补章
+#[derive(Resource, Default, Debug)]
+pub struct BrushStroke {
+    pub last: Option<Vec2>,    // 上一次盖章的位置（世界坐标），松开按键时清空
+    pub start: Option<Vec2>,   // 胶囊：按下时的位置
+    pub polygon: Vec<Vec2>,    // 多边形：已经点下的顶点
+    pub polygon_digging: bool, // 多边形：第一下是左键（挖）还是右键（填）
+    pub target: f32,           // 压平：下笔位置的密度
+}
+
+impl BrushStroke {
+    // 从上一次的位置到 to 之间按间距盖章，一帧的总量摊到重叠的章上
+    pub fn stamp_to(
+        &mut self,
+        brush: &BrushSettings,
+        map: &mut VoxelMap,
+        to: Vec2,
+        amount: f32,
+        digging: bool,
+    ) {
+        if self.last.is_none() {
+            self.target = map.sample_density(to);
+        }
+        let from = self.last.replace(to).unwrap_or(to);
+        // 间距取半径的四分之一，最小半个格子
+        let spacing = (brush.radius * VOXEL_SIZE * 0.25).max(VOXEL_SIZE * 0.5);
+        let count = ((to - from).length() / spacing).ceil().max(1.0);
+        // 线段上每个点大约会被 overlap 个章覆盖，每个章分到相应的份额
+        let overlap = (brush.radius * VOXEL_SIZE * 2.0 / spacing).floor().max(1.0);
+        let per_stamp = amount / count.min(overlap);
+
+        for i in 1..=count as i32 {
+            let center = from.lerp(to, i as f32 / count);
+            if brush.mode == BrushMode::Sculpt {
+                brush.stamp(map, center, per_stamp, digging);
+            } else {
+                brush.filter(map, center, per_stamp, self.target);
+            }
+        }
+    }
+}
+
+// 鼠标在世界坐标中的位置
+pub fn cursor_world_pos(
+    q_window: &Query<&Window>,
+    q_camera: &Query<(&Camera, &GlobalTransform)>,
+) -> Option<Vec2> {
+    let window = q_window.single().ok()?;
+    let (camera, camera_transform) = q_camera.single().ok()?;
+    let cursor_pos = window.cursor_position()?;
+    camera
+        .viewport_to_world_2d(camera_transform, cursor_pos)
+        .ok()
+}
+
+// --- 系统：Ctrl + 滚轮调半径，Shift + 滚轮调力度（不按时滚轮用来缩放相机）；
+// [ ] 和 - = 也可以调整；
+// 数字键 1-7 选材质；F 切换衰减曲线；T 切换形状；Q E 旋转长方形；M 切换模式 ---
+fn adjust_brush(
+    keys: Res<ButtonInput<KeyCode>>,
+    scroll: Res<AccumulatedMouseScroll>,
+    mut brush: ResMut<BrushSettings>,
+) {
+    let mut steps = match scroll.unit {
+        MouseScrollUnit::Line => scroll.delta.y,
+        MouseScrollUnit::Pixel => scroll.delta.y / 32.0,
+    };
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+
+    let mut radius_steps = 0.0;
+    let mut strength_steps = 0.0;
+    if keys.just_pressed(KeyCode::BracketRight) {
+        radius_steps += 1.0;
+    }
+    if keys.just_pressed(KeyCode::BracketLeft) {
+        radius_steps -= 1.0;
+    }
+    if keys.just_pressed(KeyCode::Equal) {
+        strength_steps += 1.0;
+    }
+    if keys.just_pressed(KeyCode::Minus) {
+        strength_steps -= 1.0;
+    }
+    if shift {
+        strength_steps += std::mem::take(&mut steps);
+    }
+    if ctrl {
+        radius_steps += steps;
+    }
+
+    if radius_steps != 0.0 {
+        brush.radius = (brush.radius + radius_steps)
+            .clamp(BrushSettings::MIN_RADIUS, BrushSettings::MAX_RADIUS);
+        info!("brush radius: {}", brush.radius);
+    }
+    if strength_steps != 0.0 {
+        // 力度按倍数调整，小数值时也能细调
+        brush.strength = (brush.strength * 1.25f32.powf(strength_steps))
+            .clamp(BrushSettings::MIN_STRENGTH, BrushSettings::MAX_STRENGTH);
+        info!("brush strength: {:.2}/s", brush.strength);
+    }
+
+    let digits = [
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+    ];
+    for (key, choice) in digits.into_iter().zip(Material::ALL) {
+        if keys.just_pressed(key) {
+            brush.material = choice;
+            info!("fill material: {}", choice.name());
+        }
+    }
+
+    if keys.just_pressed(KeyCode::KeyF) {
+        brush.falloff = brush.falloff.next();
+        info!("brush falloff: {:?}", brush.falloff);
+    }
+    if keys.just_pressed(KeyCode::KeyT) {
+        brush.shape = brush.shape.next();
+        info!("brush shape: {:?}", brush.shape);
+    }
+    if keys.just_pressed(KeyCode::KeyM) {
+        brush.mode = brush.mode.next();
+        info!("brush mode: {:?}", brush.mode);
+    }
+    if keys.just_pressed(KeyCode::KeyQ) {
+        brush.angle += BrushSettings::ROTATE_STEP;
+    }
+    if keys.just_pressed(KeyCode::KeyE) {
+        brush.angle -= BrushSettings::ROTATE_STEP;
+    }
+}
+
+// --- 系统：左键挖，右键填，力度按帧时间缩放，和帧率无关 ---
+fn apply_brush(
+    buttons: Res<ButtonInput<MouseButton>>,
+    time: Res<Time>,
+    brush: Res<BrushSettings>,
+    mut stroke: ResMut<BrushStroke>,
+    q_window: Query<&Window>,
+    q_camera: Query<(&Camera, &GlobalTransform)>,
+    mut map: ResMut<VoxelMap>,
+) {
+    if matches!(
+        brush.active_shape(),
+        BrushShape::Capsule | BrushShape::Polygon
+    ) {
+        return;
+    }
+
+    // 如果按下了左键(挖) 或 右键(填)；其他模式两个键效果一样
+    let is_digging = buttons.pressed(MouseButton::Left);
+    let is_building = buttons.pressed(MouseButton::Right);
+    if !is_digging && !is_building {
+        stroke.last = None;
+        return;
+    }
+    let Some(world_pos) = cursor_world_pos(&q_window, &q_camera) else {
+        stroke.last = None;
+        return;
+    };
+
+    // 直接用鼠标的精确位置，而不是取整后的格子；和上一帧之间的空隙也补上
+    let amount = brush.strength * time.delta_secs();
+    map.begin_edit(); // 一笔从按下到松开记成一步历史
+    stroke.stamp_to(&brush, &mut map, world_pos, amount, is_digging);
+}
+
+// 一次提交的量：泥土直接挖穿，硬的材质需要多画几次
+const COMMIT_AMOUNT: f32 = 1.0;
+
+// --- 系统：胶囊在松开时提交；多边形每次点击加一个顶点，回车提交，Esc 取消 ---
+fn apply_shape_brush(
+    buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    brush: Res<BrushSettings>,
+    mut stroke: ResMut<BrushStroke>,
+    q_window: Query<&Window>,
+    q_camera: Query<(&Camera, &GlobalTransform)>,
+    mut map: ResMut<VoxelMap>,
+) {
+    let cursor = cursor_world_pos(&q_window, &q_camera);
+    match brush.active_shape() {
+        BrushShape::Capsule => apply_capsule(&buttons, &brush, &mut stroke, &mut map, cursor),
+        BrushShape::Polygon => {
+            apply_polygon(&buttons, &keys, &brush, &mut stroke, &mut map, cursor)
+        }
+        _ => {}
+    }
+}
+
+fn apply_capsule(
+    buttons: &ButtonInput<MouseButton>,
+    brush: &BrushSettings,
+    stroke: &mut BrushStroke,
+    map: &mut VoxelMap,
+    cursor: Option<Vec2>,
+) {
+    if buttons.any_just_pressed([MouseButton::Left, MouseButton::Right]) {
+        stroke.start = cursor;
+    }
+    for (button, digging) in [(MouseButton::Left, true), (MouseButton::Right, false)] {
+        if buttons.just_released(button)
+            && let (Some(a), Some(b)) = (stroke.start.take(), cursor)
+        {
+            let capsule = Footprint::Capsule {
+                a,
+                b,
+                radius: brush.radius * VOXEL_SIZE,
+            };
+            map.begin_edit();
+            brush.carve(map, &capsule, COMMIT_AMOUNT, digging);
+        }
+    }
+}
+
+fn apply_polygon(
+    buttons: &ButtonInput<MouseButton>,
+    keys: &ButtonInput<KeyCode>,
+    brush: &BrushSettings,
+    stroke: &mut BrushStroke,
+    map: &mut VoxelMap,
+    cursor: Option<Vec2>,
+) {
+    for (button, digging) in [(MouseButton::Left, true), (MouseButton::Right, false)] {
+        if buttons.just_pressed(button)
+            && let Some(point) = cursor
+        {
+            if stroke.polygon.is_empty() {
+                stroke.polygon_digging = digging;
+            }
+            stroke.polygon.push(point);
+        }
+    }
+    if keys.just_pressed(KeyCode::Enter) {
+        let polygon = Footprint::Polygon(std::mem::take(&mut stroke.polygon));
+        map.begin_edit();
+        brush.carve(map, &polygon, COMMIT_AMOUNT, stroke.polygon_digging);
+    }
+    if keys.just_pressed(KeyCode::Escape) {
+        stroke.polygon.clear();
+    }
+}
+
+// --- 系统：在鼠标位置画出笔刷的范围 ---
+fn draw_brush(
+    brush: Res<BrushSettings>,
+    stroke: Res<BrushStroke>,
+    q_window: Query<&Window>,
+    q_camera: Query<(&Camera, &GlobalTransform)>,
+    mut gizmos: Gizmos,
+) {
+    let Some(world_pos) = cursor_world_pos(&q_window, &q_camera) else {
+        return;
+    };
+    let color = Color::srgba(1.0, 1.0, 1.0, 0.5);
+    let radius = brush.radius * VOXEL_SIZE;
+    match brush.active_shape() {
+        BrushShape::Square | BrushShape::Rectangle => {
+            if let Some(Footprint::Box {
+                center,
+                half_size,
+                angle,
+            }) = brush.footprint(world_pos)
+            {
+                gizmos.rect_2d(
+                    Isometry2d::new(center, Rot2::radians(angle)),
+                    half_size * 2.0,
+                    color,
+                );
+            }
+        }
+        BrushShape::Capsule => {
+            let start = stroke.start.unwrap_or(world_pos);
+            let side = (world_pos - start).normalize_or_zero().perp() * radius;
+            gizmos.circle_2d(start, radius, color);
+            gizmos.circle_2d(world_pos, radius, color);
+            gizmos.line_2d(start + side, world_pos + side, color);
+            gizmos.line_2d(start - side, world_pos - side, color);
+        }
+        BrushShape::Polygon => {
+            // 已经点下的顶点连到鼠标，再回到第一个点
+            let mut points = stroke.polygon.clone();
+            points.push(world_pos);
+            if let Some(&first) = points.first() {
+                points.push(first);
+            }
+            gizmos.linestrip_2d(points, color);
+        }
+        BrushShape::Circle => {
+            gizmos.circle_2d(world_pos, radius, color);
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use bevy::prelude::*;
+
+use crate::VOXEL_SIZE;
+
+// --- 笔刷形状：圆形以外的形状都用有符号距离场光栅化，边缘抗锯齿 ---
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum BrushShape {
+    #[default]
+    Circle,
+    Square,
+    Rectangle, // 可以旋转的长方形，长宽比 2:1
+    Capsule,   // 按下到松开之间的一条粗线，松开时提交
+    Polygon,   // 逐个点击顶点，回车提交
+}
+
+impl BrushShape {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Circle => Self::Square,
+            Self::Square => Self::Rectangle,
+            Self::Rectangle => Self::Capsule,
+            Self::Capsule => Self::Polygon,
+            Self::Polygon => Self::Circle,
+        }
+    }
+}
+
+// 放到世界坐标里的一个具体形状
+#[derive(Clone, Debug, PartialEq)]
+pub enum Footprint {
+    Box {
+        center: Vec2,
+        half_size: Vec2,
+        angle: f32, // 弧度，逆时针
+    },
+    Capsule {
+        a: Vec2,
+        b: Vec2,
+        radius: f32,
+    },
+    Polygon(Vec<Vec2>), // 顶点顺序随意，按奇偶规则判断内外
+}
+
+impl Footprint {
+    // 到边界的有符号距离（世界单位），内部为负
+    pub fn signed_distance(&self, p: Vec2) -> f32 {
+        match self {
+            Self::Box {
+                center,
+                half_size,
+                angle,
+            } => {
+                let local = Rot2::radians(-angle) * (p - *center);
+                let d = local.abs() - *half_size;
+                d.max(Vec2::ZERO).length() + d.max_element().min(0.0)
+            }
+            Self::Capsule { a, b, radius } => segment_distance(p, *a, *b) - radius,
+            Self::Polygon(points) => {
+                let mut dist = f32::MAX;
+                let mut inside = false;
+                for (i, &a) in points.iter().enumerate() {
+                    let b = points[(i + 1) % points.len()];
+                    dist = dist.min(segment_distance(p, a, b));
+                    // 向 +x 发出的射线穿过这条边的次数决定内外
+                    if (a.y > p.y) != (b.y > p.y)
+                        && p.x < a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x)
+                    {
+                        inside = !inside;
+                    }
+                }
+                if inside { -dist } else { dist }
+            }
+        }
+    }
+
+    // 世界坐标的包围盒 (min, max)
+    pub fn bounds(&self) -> (Vec2, Vec2) {
+        match self {
+            Self::Box {
+                center,
+                half_size,
+                angle,
+            } => {
+                let rot = Rot2::radians(*angle);
+                let extent = (rot * *half_size)
+                    .abs()
+                    .max((rot * half_size.with_x(-half_size.x)).abs());
+                (*center - extent, *center + extent)
+            }
+            Self::Capsule { a, b, radius } => (
+                a.min(*b) - Vec2::splat(*radius),
+                a.max(*b) + Vec2::splat(*radius),
+            ),
+            Self::Polygon(points) => points.iter().fold(
+                (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+                |(min, max), p| (min.min(*p), max.max(*p)),
+            ),
+        }
+    }
+
+    // 格点的覆盖率 0..1：边界上正好是 0.5，向两侧各一个格子线性过渡，
+    // 这样 Marching Squares 插值出来的边正好落在形状的边界上
+    pub fn coverage(&self, p: Vec2) -> f32 {
+        (0.5 - self.signed_distance(p) / (2.0 * VOXEL_SIZE)).clamp(0.0, 1.0)
+    }
+
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Self::Polygon(points) => points.len() >= 3,
+            _ => true,
+        }
+    }
+}
+
+// 点到线段的距离
+fn segment_distance(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    let t = ((p - a).dot(ab) / ab.length_squared().max(f32::EPSILON)).clamp(0.0, 1.0);
+    p.distance(a + ab * t)
+}
@@ -0,0 +1,166 @@
+use bevy::camera::CameraUpdateSystems;
+use bevy::input::mouse::{AccumulatedMouseScroll, MouseScrollUnit};
+use bevy::prelude::*;
+use bevy::transform::TransformSystems;
+
+// --- 相机控制：中键拖动平移，滚轮以鼠标为中心缩放，WASD 平移，Home 复位 ---
+// 在 PostUpdate 里、变换传播之前移动相机，下一帧 Update 里
+// viewport_to_world_2d 用到的变换和投影都已经是新的，编辑不会错位
+pub struct CameraControlPlugin;
+
+impl Plugin for CameraControlPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraControls>().add_systems(
+            PostUpdate,
+            (pan_camera, zoom_camera, reset_camera)
+                .chain()
+                .before(TransformSystems::Propagate)
+                .before(CameraUpdateSystems),
+        );
+    }
+}
+
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct CameraControls {
+    pub min_zoom: f32,  // 投影缩放的下限（越小放得越大）
+    pub max_zoom: f32,  // 投影缩放的上限
+    pub zoom_step: f32, // 滚轮每一格缩放的倍数
+    pub pan_speed: f32, // 键盘平移速度（屏幕像素每秒，和缩放无关）
+}
+
+impl Default for CameraControls {
+    fn default() -> Self {
+        Self {
+            min_zoom: 0.1,
+            max_zoom: 4.0,
+            zoom_step: 1.15,
+            pan_speed: 600.0,
+        }
+    }
+}
+
+// 相机当前能看到的世界范围，用来剔除屏幕外的区块
+pub fn visible_world_rect(camera: &Camera, transform: &GlobalTransform) -> Option<Rect> {
+    let viewport = camera.logical_viewport_rect()?;
+    let a = camera.viewport_to_world_2d(transform, viewport.min).ok()?;
+    let b = camera.viewport_to_world_2d(transform, viewport.max).ok()?;
+    Some(Rect::from_corners(a, b))
+}
+
+// 正交投影当前的缩放，其他投影当作 1
+fn ortho_scale(projection: &Projection) -> f32 {
+    match projection {
+        Projection::Orthographic(ortho) => ortho.scale,
+        _ => 1.0,
+    }
+}
+
+// --- 系统：按住中键拖动，或者按 WASD 平移 ---
+// 拖动用光标在窗口里的位置（逻辑像素）而不是鼠标的原始位移，
+// 有指针加速或者高分屏缩放时，抓住的那一点也始终跟着光标
+fn pan_camera(
+    buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    controls: Res<CameraControls>,
+    q_window: Query<&Window>,
+    mut q_camera: Query<(&mut Transform, &Projection), With<Camera2d>>,
+    mut last_cursor: Local<Option<Vec2>>,
+) {
+    let Ok((mut transform, projection)) = q_camera.single_mut() else {
+        return;
+    };
+    let cursor = q_window.single().ok().and_then(Window::cursor_position);
+    let dragging = buttons.pressed(MouseButton::Middle);
+    let previous = std::mem::replace(&mut *last_cursor, cursor.filter(|_| dragging));
+    // Ctrl 留给撤销之类的快捷键，按着的时候不动相机
+    if keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+
+    // 屏幕坐标的 y 轴向下，世界坐标向上
+    let mut delta = Vec2::ZERO;
+    if let (true, Some(previous), Some(cursor)) = (dragging, previous, cursor) {
+        delta += Vec2::new(previous.x - cursor.x, cursor.y - previous.y);
+    }
+    let mut direction = Vec2::ZERO;
+    for (key, dir) in [
+        (KeyCode::KeyW, Vec2::Y),
+        (KeyCode::KeyS, Vec2::NEG_Y),
+        (KeyCode::KeyA, Vec2::NEG_X),
+        (KeyCode::KeyD, Vec2::X),
+    ] {
+        if keys.pressed(key) {
+            direction += dir;
+        }
+    }
+    delta += direction.normalize_or_zero() * controls.pan_speed * time.delta_secs();
+
+    if delta != Vec2::ZERO {
+        // 屏幕上的一个像素等于 scale 个世界单位
+        transform.translation += (delta * ortho_scale(projection)).extend(0.0);
+    }
+}
+
+// --- 系统：滚轮缩放，鼠标下面的那个世界坐标保持不动 ---
+// 按着 Shift 或 Ctrl 时滚轮归画笔调力度和半径
+fn zoom_camera(
+    keys: Res<ButtonInput<KeyCode>>,
+    scroll: Res<AccumulatedMouseScroll>,
+    controls: Res<CameraControls>,
+    q_window: Query<&Window>,
+    mut q_camera: Query<(&mut Transform, &mut Projection), With<Camera2d>>,
+) {
+    if keys.any_pressed([
+        KeyCode::ShiftLeft,
+        KeyCode::ShiftRight,
+        KeyCode::ControlLeft,
+        KeyCode::ControlRight,
+    ]) {
+        return;
+    }
+    let steps = match scroll.unit {
+        MouseScrollUnit::Line => scroll.delta.y,
+        MouseScrollUnit::Pixel => scroll.delta.y / 32.0,
+    };
+    if steps == 0.0 {
+        return;
+    }
+    let Ok((mut transform, mut projection)) = q_camera.single_mut() else {
+        return;
+    };
+    let Projection::Orthographic(ortho) = projection.as_mut() else {
+        return;
+    };
+
+    let old = ortho.scale;
+    let new = (old / controls.zoom_step.powf(steps)).clamp(controls.min_zoom, controls.max_zoom);
+    ortho.scale = new;
+
+    // 鼠标相对窗口中心的偏移（世界方向），缩放前后对应同一个世界坐标
+    let Ok(window) = q_window.single() else {
+        return;
+    };
+    if let Some(cursor) = window.cursor_position() {
+        let offset = (cursor - window.size() / 2.0) * Vec2::new(1.0, -1.0);
+        transform.translation += (offset * (old - new)).extend(0.0);
+    }
+}
+
+// --- 系统：Home 把相机移回原点、恢复默认缩放 ---
+fn reset_camera(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut q_camera: Query<(&mut Transform, &mut Projection), With<Camera2d>>,
+) {
+    if !keys.just_pressed(KeyCode::Home) {
+        return;
+    }
+    let Ok((mut transform, mut projection)) = q_camera.single_mut() else {
+        return;
+    };
+    transform.translation.x = 0.0;
+    transform.translation.y = 0.0;
+    if let Projection::Orthographic(ortho) = projection.as_mut() {
+        ortho.scale = 1.0;
+    }
+}
@@ -0,0 +1,186 @@
+use bevy::platform::collections::{HashMap, HashSet};
+use bevy::prelude::*;
+
+use crate::marching_squares::{self, SaddleResolution};
+use crate::material::Material;
+use crate::voxel_map::VoxelMap;
+
+// --- 轮廓提取：把每个格子的线段首尾相连成折线 ---
+
+// 一条轮廓线
+// 方向统一为“实心在左手边”：实心岛屿逆时针，洞顺时针
+#[derive(Clone, Debug, PartialEq)]
+pub struct Contour {
+    pub points: Vec<Vec2>,
+    pub closed: bool, // 闭合的环（最后一个点连回第一个点，但不重复存储）
+}
+
+impl Contour {
+    // 鞋带公式求有向面积，逆时针为正；开放的折线按首尾相连计算
+    pub fn signed_area(&self) -> f32 {
+        let n = self.points.len();
+        (0..n)
+            .map(|i| self.points[i].perp_dot(self.points[(i + 1) % n]))
+            .sum::<f32>()
+            / 2.0
+    }
+
+    // 实心岛屿的外轮廓
+    pub fn is_island(&self) -> bool {
+        self.closed && self.signed_area() > 0.0
+    }
+
+    // 实心内部的洞
+    pub fn is_hole(&self) -> bool {
+        self.closed && self.signed_area() < 0.0
+    }
+
+    // 依次返回每条线段（闭合的环包含最后一段）
+    pub fn segments(&self) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+        let n = self.points.len();
+        let count = if self.closed { n } else { n.saturating_sub(1) };
+        (0..count).map(move |i| (self.points[i], self.points[(i + 1) % n]))
+    }
+}
+
+// 提取整张地图的轮廓，地图外面都是空气，所以结果全是闭合的环
+// saddle 和渲染用的一致时，轮廓和屏幕上画出来的墙完全重合
+pub fn extract_contours(map: &VoxelMap, iso: f32, saddle: SaddleResolution) -> Vec<Contour> {
+    let Some((min, max)) = map.bounds() else {
+        return Vec::new();
+    };
+    // 向外多走一格，让地图边缘也能闭合
+    extract_contours_in(map, min - IVec2::ONE, max - IVec2::ONE, iso, saddle)
+}
+
+// 只提取 [min, max] 范围内的格子（都包含在内），碰到范围边缘的轮廓是开放的折线
+pub fn extract_contours_in(
+    map: &VoxelMap,
+    min: IVec2,
+    max: IVec2,
+    iso: f32,
+    saddle: SaddleResolution,
+) -> Vec<Contour> {
+    extract_with(
+        min,
+        max,
+        iso,
+        saddle,
+        |x, y| map.get_density(x, y),
+        |x, y| map.grid_to_world(x, y),
+    )
+}
+
+// 只提取某一种材质的轮廓：别的材质的墙当作密度 0，空气保持原来的密度，
+// 这样和空气之间的边界与总轮廓重合，两种材质之间的边界落在格点中间
+pub fn extract_material_contours(
+    map: &VoxelMap,
+    iso: f32,
+    material: Material,
+    saddle: SaddleResolution,
+) -> Vec<Contour> {
+    let Some((min, max)) = map.bounds() else {
+        return Vec::new();
+    };
+    extract_with(
+        min - IVec2::ONE,
+        max - IVec2::ONE,
+        iso,
+        saddle,
+        |x, y| {
+            let density = map.get_density(x, y);
+            if density < iso || map.get_material(x, y) == material {
+                density
+            } else {
+                0.0
+            }
+        },
+        |x, y| map.grid_to_world(x, y),
+    )
+}
+
+type EdgeKey = (IVec2, bool);
+
+// 真正的提取逻辑：密度和坐标都通过闭包读取
+pub(crate) fn extract_with(
+    min: IVec2,
+    max: IVec2,
+    iso: f32,
+    saddle: SaddleResolution,
+    density: impl Fn(i32, i32) -> f32,
+    position: impl Fn(i32, i32) -> Vec2,
+) -> Vec<Contour> {
+    // 1. 收集所有线段，用边的编号表示端点
+    let mut order = Vec::new();
+    let mut next: HashMap<EdgeKey, EdgeKey> = HashMap::default();
+    for y in min.y..=max.y {
+        for x in min.x..=max.x {
+            let values = [
+                density(x, y),
+                density(x + 1, y),
+                density(x + 1, y + 1),
+                density(x, y + 1),
+            ];
+            for (start, end) in marching_squares::march_cell_edges(values, iso, saddle).iter() {
+                let start = start.key(x, y);
+                order.push(start);
+                next.insert(start, end.key(x, y));
+            }
+        }
+    }
+
+    // 2. 边上的插值点：同一条边不管从哪个格子算，结果都完全一样
+    let point = |(corner, vertical): EdgeKey| {
+        let other = if vertical {
+            corner + IVec2::Y
+        } else {
+            corner + IVec2::X
+        };
+        marching_squares::interpolate(
+            position(corner.x, corner.y),
+            position(other.x, other.y),
+            density(corner.x, corner.y),
+            density(other.x, other.y),
+            iso,
+        )
+    };
+
+    let mut contours = Vec::new();
+
+    // 3. 先走开放的折线：起点不是任何线段的终点
+    let ends: HashSet<EdgeKey> = next.values().copied().collect();
+    for &start in &order {
+        if ends.contains(&start) || !next.contains_key(&start) {
+            continue;
+        }
+        let mut points = vec![point(start)];
+        let mut current = start;
+        while let Some(following) = next.remove(&current) {
+            points.push(point(following));
+            current = following;
+        }
+        contours.push(Contour {
+            points,
+            closed: false,
+        });
+    }
+
+    // 4. 剩下的都是闭合的环
+    for &start in &order {
+        if !next.contains_key(&start) {
+            continue;
+        }
+        let mut points = Vec::new();
+        let mut current = start;
+        while let Some(following) = next.remove(&current) {
+            points.push(point(current));
+            current = following;
+        }
+        contours.push(Contour {
+            points,
+            closed: true,
+        });
+    }
+
+    contours
+}
@@ -0,0 +1,162 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::material::Material;
+use crate::render::update_contour_cache;
+use crate::voxel_map::VoxelMap;
+
+// --- 撤销/重做：每一笔记录成一份只包含改动格点的增量 ---
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditHistory>()
+            // 笔刷在 Update 里修改地图，这里等它们都跑完再收尾，撤销的结果当帧就能画出来
+            .add_systems(
+                PostUpdate,
+                (commit_edit, undo_redo)
+                    .chain()
+                    .before(update_contour_cache),
+            );
+    }
+}
+
+// 一个格点的变化
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CellChange {
+    pub cell: IVec2,
+    pub before: (f32, Material), // (密度, 材质)
+    pub after: (f32, Material),
+}
+
+// 一次编辑的增量
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EditDelta {
+    pub changes: Vec<CellChange>,
+    pub mined: [f32; Material::ALL.len()], // 这一步每种材质挖掉的量，撤销时从 VoxelMap::mined 里减掉
+}
+
+impl EditDelta {
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl VoxelMap {
+    // 把增量应用到地图上：undo 为 true 时写回修改前的值
+    pub fn apply_delta(&mut self, delta: &EditDelta, undo: bool) {
+        for change in &delta.changes {
+            let (density, material) = if undo { change.before } else { change.after };
+            self.set_voxel(change.cell.x, change.cell.y, density, material);
+        }
+        self.add_mined(&delta.mined, if undo { -1.0 } else { 1.0 });
+    }
+}
+
+// 编辑历史，最多保留 capacity 步，超出时丢掉最早的
+#[derive(Resource, Debug)]
+pub struct EditHistory {
+    undo: VecDeque<EditDelta>,
+    redo: Vec<EditDelta>,
+    capacity: usize,
+}
+
+impl Default for EditHistory {
+    fn default() -> Self {
+        Self::new(64)
+    }
+}
+
+impl EditHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    // 记录新的一步，之前撤销掉的步骤不能再重做
+    pub fn push(&mut self, delta: EditDelta) {
+        if delta.is_empty() {
+            return;
+        }
+        self.redo.clear();
+        if self.undo.len() == self.capacity {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(delta);
+    }
+
+    // 撤销最近的一步，没有可撤销的步骤时返回 false
+    pub fn undo(&mut self, map: &mut VoxelMap) -> bool {
+        let Some(delta) = self.undo.pop_back() else {
+            return false;
+        };
+        map.apply_delta(&delta, true);
+        self.redo.push(delta);
+        true
+    }
+
+    pub fn redo(&mut self, map: &mut VoxelMap) -> bool {
+        let Some(delta) = self.redo.pop() else {
+            return false;
+        };
+        map.apply_delta(&delta, false);
+        self.undo.push_back(delta);
+        true
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+// --- 系统：鼠标松开后，把这一笔的改动收进历史 ---
+fn commit_edit(
+    buttons: Res<ButtonInput<MouseButton>>,
+    mut map: ResMut<VoxelMap>,
+    mut history: ResMut<EditHistory>,
+) {
+    if !map.is_recording() || buttons.any_pressed([MouseButton::Left, MouseButton::Right]) {
+        return;
+    }
+    if let Some(delta) = map.end_edit() {
+        history.push(delta);
+    }
+}
+
+// --- 系统：Ctrl+Z 撤销，Ctrl+Shift+Z 重做 ---
+fn undo_redo(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut map: ResMut<VoxelMap>,
+    mut history: ResMut<EditHistory>,
+) {
+    let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    if !ctrl || !keys.just_pressed(KeyCode::KeyZ) || map.is_recording() {
+        return;
+    }
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let done = if shift {
+        history.redo(&mut map)
+    } else {
+        history.undo(&mut map)
+    };
+    if done {
+        info!("{}", if shift { "redo" } else { "undo" });
+    }
+}
@@ -0,0 +1,127 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+use image::imageops::{self, FilterType};
+use image::{GrayImage, ImageResult, Luma};
+
+use crate::VOXEL_SIZE;
+use crate::history::EditHistory;
+use crate::voxel_map::VoxelMap;
+
+// --- 灰度图导入/导出：像素亮度 <-> 密度，白色是实心，黑色是空气 ---
+// 图片的第一行是最上面一行，网格的 y 轴向上，读写时上下翻转
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ImageImport {
+    pub size: Option<UVec2>, // 缩放到指定的网格大小（双线性），None 表示一个像素一个格点
+    pub invert: bool,        // 黑色是实心
+}
+
+impl VoxelMap {
+    // 从灰度图创建地图，和 VoxelMap::new 一样在世界原点居中
+    pub fn from_image(image: &GrayImage, options: ImageImport) -> Self {
+        let resized;
+        let image = match options.size {
+            Some(size) if size != UVec2::from(image.dimensions()) => {
+                resized =
+                    imageops::resize(image, size.x.max(1), size.y.max(1), FilterType::Triangle);
+                &resized
+            }
+            _ => image,
+        };
+
+        let (width, height) = image.dimensions();
+        let origin = -Vec2::new(width as f32, height as f32) * VOXEL_SIZE / 2.0;
+        let mut map = Self::empty(origin);
+        for (px, py, Luma([luma])) in image.enumerate_pixels() {
+            let mut density = *luma as f32 / u8::MAX as f32;
+            if options.invert {
+                density = 1.0 - density;
+            }
+            map.set_density(px as i32, (height - 1 - py) as i32, density);
+        }
+        map
+    }
+
+    // 导出图片覆盖的格子范围 (min, max)，max 不包含
+    // VoxelMap::new、世界生成和 from_image 得到的地图都以原点居中，格子从 0 开始，
+    // 从 origin 就能推出当初的大小；导出这整块（再并上超出它的非空格子），
+    // 边缘是空气时图片也不会变小，导入后按同样的大小居中，格子坐标和原点都不变
+    pub fn image_bounds(&self) -> Option<(IVec2, IVec2)> {
+        let size = (-self.origin() * 2.0 / VOXEL_SIZE).round().as_ivec2();
+        let generated = size.cmpgt(IVec2::ZERO).all().then_some((IVec2::ZERO, size));
+        match (generated, self.content_bounds()) {
+            (Some((a_min, a_max)), Some((b_min, b_max))) => {
+                Some((a_min.min(b_min), a_max.max(b_max)))
+            }
+            (bounds, None) | (None, bounds) => bounds,
+        }
+    }
+
+    // 把 image_bounds 的范围画成灰度图，什么都没有时返回 1x1 的黑图
+    // 非空格子超出当初的大小时（比如在地图外面又填了东西），导入后的位置会跟着偏移
+    pub fn to_image(&self) -> GrayImage {
+        let Some((min, max)) = self.image_bounds() else {
+            return GrayImage::new(1, 1);
+        };
+        let size = max - min;
+        GrayImage::from_fn(size.x as u32, size.y as u32, |px, py| {
+            let density = self.get_density(min.x + px as i32, max.y - 1 - py as i32);
+            Luma([(density * u8::MAX as f32).round() as u8])
+        })
+    }
+
+    pub fn import_png(path: impl AsRef<Path>, options: ImageImport) -> ImageResult<Self> {
+        let image = image::open(path)?.into_luma8();
+        Ok(Self::from_image(&image, options))
+    }
+
+    pub fn export_png(&self, path: impl AsRef<Path>) -> ImageResult<()> {
+        self.to_image().save(path)
+    }
+}
+
+// --- 插件：F6 导出灰度图，F8 从灰度图导入 ---
+pub struct ImageIoPlugin;
+
+impl Plugin for ImageIoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ImagePath>()
+            .add_systems(Update, image_keys);
+    }
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct ImagePath(pub String);
+
+impl Default for ImagePath {
+    fn default() -> Self {
+        Self("terrain.png".to_string())
+    }
+}
+
+fn image_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    path: Res<ImagePath>,
+    mut map: ResMut<VoxelMap>,
+    mut history: Option<ResMut<EditHistory>>,
+) {
+    if keys.just_pressed(KeyCode::F6) {
+        match map.export_png(&path.0) {
+            Ok(()) => info!("exported density map to {}", path.0),
+            Err(err) => error!("failed to export {}: {err}", path.0),
+        }
+    }
+    if keys.just_pressed(KeyCode::F8) {
+        match VoxelMap::import_png(&path.0, ImageImport::default()) {
+            Ok(imported) => {
+                map.replace(imported);
+                if let Some(history) = history.as_mut() {
+                    history.clear();
+                }
+                info!("imported {} chunks from {}", map.chunk_count(), path.0);
+            }
+            Err(err) => error!("failed to import {}: {err}", path.0),
+        }
+    }
+}
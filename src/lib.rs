@@ -0,0 +1,25 @@
+pub mod brush;
+pub mod brush_shape;
+pub mod camera;
+pub mod contour;
+pub mod history;
+pub mod image_io;
+pub mod marching_squares;
+pub mod material;
+pub mod noise;
+#[cfg(feature = "physics")]
+pub mod physics;
+pub mod raycast;
+pub mod render;
+pub mod save;
+pub mod simplify;
+pub mod svg;
+pub mod voxel_map;
+pub mod worldgen;
+
+// --- 1. 配置常量 ---
+pub const VOXEL_SIZE: f32 = 8.0; // 每个格子的大小
+pub const GRID_WIDTH: usize = 100; // 初始地图宽（格子数）
+pub const GRID_HEIGHT: usize = 80; // 初始地图高（格子数）
+pub const CHUNK_SIZE: i32 = 16; // 每个区块的边长（格子数）
+pub const ISO_LEVEL: f32 = 0.5; // 阈值：密度 > 0.5 认为是墙，< 0.5 是空气
@@ -0,0 +1,450 @@
+use bevy::prelude::*;
+
+use crate::VOXEL_SIZE;
+use crate::material::Material;
+use crate::voxel_map::VoxelMap;
+
+// --- Marching Squares 单个格子的计算 ---
+// 格子的四个角按 左下(0) 右下(1) 右上(2) 左上(3) 的顺序排列
+
+// 鞍点（情况 5 和 10）的处理方式：两个对角的实心部分是连在一起还是分开
+#[derive(Resource, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum SaddleResolution {
+    Legacy,       // 旧的行为：总是分开
+    CenterSample, // 用四个角的平均值作为中心密度
+    #[default]
+    AsymptoticDecider, // 用双线性插值在鞍点处的值作为中心密度
+}
+
+impl SaddleResolution {
+    // 依次切换，方便对比效果
+    pub fn next(self) -> Self {
+        match self {
+            Self::Legacy => Self::CenterSample,
+            Self::CenterSample => Self::AsymptoticDecider,
+            Self::AsymptoticDecider => Self::Legacy,
+        }
+    }
+
+    // 中心是否是实心：是的话两个实心角连通
+    pub fn connects(self, values: [f32; 4], iso: f32) -> bool {
+        let [v0, v1, v2, v3] = values;
+        let center = match self {
+            Self::Legacy => return false,
+            Self::CenterSample => (v0 + v1 + v2 + v3) / 4.0,
+            Self::AsymptoticDecider => {
+                let denom = v0 + v2 - v1 - v3;
+                if denom.abs() < 0.0001 {
+                    (v0 + v1 + v2 + v3) / 4.0
+                } else {
+                    (v0 * v2 - v1 * v3) / denom
+                }
+            }
+        };
+        center >= iso
+    }
+}
+
+// 格子的四条边，第 i 条边连接角 i 和角 i + 1
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum CellEdge {
+    #[default]
+    Bottom,
+    Right,
+    Top,
+    Left,
+}
+
+impl CellEdge {
+    // 这条边在全局网格里的编号：(起点格点, 是否竖直)
+    // 相邻两个格子共用的边编号相同，用来把线段首尾相连
+    pub fn key(self, x: i32, y: i32) -> (IVec2, bool) {
+        match self {
+            Self::Bottom => (IVec2::new(x, y), false),
+            Self::Top => (IVec2::new(x, y + 1), false),
+            Self::Left => (IVec2::new(x, y), true),
+            Self::Right => (IVec2::new(x + 1, y), true),
+        }
+    }
+}
+
+// 一个格子里的线段用边来表示，方向统一为“实心在左手边”，
+// 这样实心岛屿的轮廓是逆时针的，洞的轮廓是顺时针的
+#[derive(Clone, Copy, Default)]
+pub struct CellEdges {
+    pairs: [(CellEdge, CellEdge); 2],
+    len: usize,
+}
+
+impl CellEdges {
+    fn push(&mut self, start: CellEdge, end: CellEdge) {
+        self.pairs[self.len] = (start, end);
+        self.len += 1;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (CellEdge, CellEdge)> + '_ {
+        self.pairs[..self.len].iter().copied()
+    }
+}
+
+// 一个格子最多产生两条线段（鞍点情况 5 和 10）
+#[derive(Clone, Copy, Default)]
+pub struct CellSegments {
+    segments: [[Vec2; 2]; 2],
+    len: usize,
+}
+
+impl CellSegments {
+    fn push(&mut self, start: Vec2, end: Vec2) {
+        self.segments[self.len] = [start, end];
+        self.len += 1;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = [Vec2; 2]> + '_ {
+        self.segments[..self.len].iter().copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+// 读取以 (x, y) 为左下角的格子的四个角坐标和密度
+pub fn cell_corners(map: &VoxelMap, x: i32, y: i32) -> ([Vec2; 4], [f32; 4]) {
+    let p0 = map.grid_to_world(x, y); // 左下
+    let points = [
+        p0,
+        p0 + Vec2::new(VOXEL_SIZE, 0.0),        // 右下
+        p0 + Vec2::new(VOXEL_SIZE, VOXEL_SIZE), // 右上
+        p0 + Vec2::new(0.0, VOXEL_SIZE),        // 左上
+    ];
+    let values = [
+        map.get_density(x, y),
+        map.get_density(x + 1, y),
+        map.get_density(x + 1, y + 1),
+        map.get_density(x, y + 1),
+    ];
+    (points, values)
+}
+
+// 读取格子四个角的材质，顺序和 cell_corners 一样
+pub fn cell_materials(map: &VoxelMap, x: i32, y: i32) -> [Material; 4] {
+    [
+        map.get_material(x, y),
+        map.get_material(x + 1, y),
+        map.get_material(x + 1, y + 1),
+        map.get_material(x, y + 1),
+    ]
+}
+
+// 计算“状态码” (Case Index)
+// 二进制编码：如果角是墙(>= 阈值)设为1，否则为0
+pub fn case_index(values: [f32; 4], iso: f32) -> u8 {
+    let mut case_index = 0;
+    for (bit, value) in values.iter().enumerate() {
+        if *value >= iso {
+            case_index |= 1 << bit;
+        }
+    }
+    case_index
+}
+
+// 根据状态码查表得到格子内的线段 (这是 Marching Squares 的标准查找表逻辑)
+pub fn march_cell_edges(values: [f32; 4], iso: f32, saddle: SaddleResolution) -> CellEdges {
+    use CellEdge::{Bottom as D, Left as A, Right as C, Top as B};
+
+    let mut out = CellEdges::default();
+    match case_index(values, iso) {
+        1 => out.push(D, A),
+        2 => out.push(C, D),
+        3 => out.push(C, A),
+        4 => out.push(B, C),
+        5 if saddle.connects(values, iso) => {
+            out.push(D, C);
+            out.push(B, A);
+        }
+        5 => {
+            out.push(D, A);
+            out.push(B, C);
+        }
+        6 => out.push(B, D),
+        7 => out.push(B, A),
+        8 => out.push(A, B),
+        9 => out.push(D, B),
+        10 if saddle.connects(values, iso) => {
+            out.push(A, D);
+            out.push(C, B);
+        }
+        10 => {
+            out.push(C, D);
+            out.push(A, B);
+        }
+        11 => out.push(C, B),
+        12 => out.push(A, C),
+        13 => out.push(D, C),
+        14 => out.push(A, D),
+        // 如果全空(0)或全满(15)，不需要画线
+        _ => {}
+    }
+    out
+}
+
+// 【平滑的关键】计算四条边上的插值点
+// 我们不取边的中点，而是根据密度比例计算准确位置
+pub fn edge_points(points: [Vec2; 4], values: [f32; 4], iso: f32) -> [Vec2; 4] {
+    let [p0, p1, p2, p3] = points;
+    let [v0, v1, v2, v3] = values;
+    [
+        interpolate(p0, p1, v0, v1, iso), // 下边
+        interpolate(p1, p2, v1, v2, iso), // 右边
+        interpolate(p3, p2, v3, v2, iso), // 上边
+        interpolate(p0, p3, v0, v3, iso), // 左边
+    ]
+}
+
+// 查表后把边换成具体的坐标，得到可以直接绘制的线段
+pub fn march_cell(
+    points: [Vec2; 4],
+    values: [f32; 4],
+    iso: f32,
+    saddle: SaddleResolution,
+) -> CellSegments {
+    let edges = edge_points(points, values, iso);
+    let mut out = CellSegments::default();
+    for (start, end) in march_cell_edges(values, iso, saddle).iter() {
+        out.push(edges[start as usize], edges[end as usize]);
+    }
+    out
+}
+
+// 一个格子的实心区域：最多两个凸多边形（不连通的鞍点），每个最多 6 个点
+#[derive(Clone, Copy, Default)]
+pub struct CellPolygons {
+    points: [[Vec2; 6]; 2],
+    lens: [usize; 2],
+    count: usize,
+}
+
+impl CellPolygons {
+    fn push(&mut self, polygon: &[Vec2]) {
+        self.points[self.count][..polygon.len()].copy_from_slice(polygon);
+        self.lens[self.count] = polygon.len();
+        self.count += 1;
+    }
+
+    // 每个多边形都是逆时针顺序
+    pub fn iter(&self) -> impl Iterator<Item = &[Vec2]> + '_ {
+        (0..self.count).map(|i| &self.points[i][..self.lens[i]])
+    }
+}
+
+// 求格子的实心区域
+// 沿着 左下 -> 下边 -> 右下 -> 右边 -> 右上 -> 上边 -> 左上 -> 左边 逆时针走一圈，
+// 保留实心的角和状态发生变化的边上的插值点
+pub fn cell_polygons(
+    points: [Vec2; 4],
+    values: [f32; 4],
+    iso: f32,
+    saddle: SaddleResolution,
+) -> CellPolygons {
+    let mut out = CellPolygons::default();
+    let case_index = case_index(values, iso);
+    if case_index == 0 {
+        return out;
+    }
+
+    let solid = |i: usize| case_index & (1 << (i % 4)) != 0;
+    let edges = edge_points(points, values, iso);
+
+    // 鞍点且中心不连通：两个对角各自是一个独立的三角形
+    if (case_index == 5 || case_index == 10) && !saddle.connects(values, iso) {
+        for i in (0..4).filter(|&i| solid(i)) {
+            out.push(&[edges[(i + 3) % 4], points[i], edges[i]]);
+        }
+        return out;
+    }
+
+    // 其余情况都是正方形切掉一到两个角，是凸多边形
+    let start = (0..4).find(|&i| solid(i)).unwrap_or(0);
+    let mut polygon = [Vec2::ZERO; 6];
+    let mut len = 0;
+    for step in 0..4 {
+        let i = (start + step) % 4;
+        if solid(i) {
+            polygon[len] = points[i];
+            len += 1;
+        }
+        if solid(i) != solid(i + 1) {
+            polygon[len] = edges[i];
+            len += 1;
+        }
+    }
+    out.push(&polygon[..len]);
+    out
+}
+
+// --- 多材质：实心区域再按材质切开 ---
+
+// 带材质的三角形，以及不同材质之间的分界线
+#[derive(Default)]
+pub struct MaterialCell {
+    pub triangles: Vec<([Vec2; 3], Material)>,
+    pub boundaries: Vec<[Vec2; 2]>,
+}
+
+// 空气角的材质没有意义，借用相邻（其次是对角）实心角的材质，
+// 这样只有一种实心材质的格子不会被切开
+pub fn resolve_materials(values: [f32; 4], materials: [Material; 4], iso: f32) -> [Material; 4] {
+    let mut out = materials;
+    for (i, material) in out.iter_mut().enumerate() {
+        if values[i] >= iso {
+            continue;
+        }
+        if let Some(j) = [(i + 3) % 4, (i + 1) % 4, (i + 2) % 4]
+            .into_iter()
+            .find(|&j| values[j] >= iso)
+        {
+            *material = materials[j];
+        }
+    }
+    out
+}
+
+// 先按密度求出实心区域，再用每种材质的“归属”场切分它
+// 材质之间的分界线落在相邻两个角的中点，和空气的边界仍然是密度插值的结果
+pub fn material_cell(
+    points: [Vec2; 4],
+    values: [f32; 4],
+    materials: [Material; 4],
+    iso: f32,
+    saddle: SaddleResolution,
+) -> MaterialCell {
+    let mut out = MaterialCell::default();
+    let solid = cell_polygons(points, values, iso, saddle);
+    let resolved = resolve_materials(values, materials, iso);
+    let mut kinds = resolved.to_vec();
+    kinds.sort();
+    kinds.dedup();
+
+    // 只有一种材质：直接使用实心区域
+    if kinds.len() == 1 {
+        for polygon in solid.iter() {
+            push_fan(&mut out.triangles, polygon, kinds[0]);
+        }
+        return out;
+    }
+
+    // 三种以上的材质：每个角占据自己的四分之一格子
+    if kinds.len() > 2 {
+        let center = (points[0] + points[2]) / 2.0;
+        let mid = |i: usize| (points[i] + points[(i + 1) % 4]) / 2.0;
+        for i in 0..4 {
+            let quadrant = [points[i], mid(i), center, mid((i + 3) % 4)];
+            for polygon in solid.iter() {
+                push_fan(
+                    &mut out.triangles,
+                    &clip_convex(&quadrant, polygon),
+                    resolved[i],
+                );
+            }
+            if resolved[i] != resolved[(i + 1) % 4] {
+                for polygon in solid.iter() {
+                    out.boundaries.extend(clip_segment(center, mid(i), polygon));
+                }
+            }
+        }
+        return out;
+    }
+
+    for (k, &material) in kinds.iter().enumerate() {
+        let mask = resolved.map(|m| if m == material { 1.0 } else { 0.0 });
+        // 两种材质的区域互补：编号小的在鞍点处连通，另一种保持分开
+        let mask_saddle = if k == 0 {
+            SaddleResolution::CenterSample
+        } else {
+            SaddleResolution::Legacy
+        };
+        for region in cell_polygons(points, mask, 0.5, mask_saddle).iter() {
+            for polygon in solid.iter() {
+                push_fan(&mut out.triangles, &clip_convex(region, polygon), material);
+            }
+        }
+        // 分界线只需要从其中一侧画一次
+        if k == 0 {
+            for [a, b] in march_cell(points, mask, 0.5, mask_saddle).iter() {
+                for polygon in solid.iter() {
+                    out.boundaries.extend(clip_segment(a, b, polygon));
+                }
+            }
+        }
+    }
+    out
+}
+
+fn push_fan(out: &mut Vec<([Vec2; 3], Material)>, polygon: &[Vec2], material: Material) {
+    for i in 1..polygon.len().saturating_sub(1) {
+        out.push(([polygon[0], polygon[i], polygon[i + 1]], material));
+    }
+}
+
+// Sutherland–Hodgman：用凸多边形 clip 裁剪 subject（都是逆时针）
+fn clip_convex(subject: &[Vec2], clip: &[Vec2]) -> Vec<Vec2> {
+    let mut output = subject.to_vec();
+    for i in 0..clip.len() {
+        if output.is_empty() {
+            break;
+        }
+        let (a, b) = (clip[i], clip[(i + 1) % clip.len()]);
+        let side = |p: Vec2| (b - a).perp_dot(p - a);
+        let input = std::mem::take(&mut output);
+        for j in 0..input.len() {
+            let (p, q) = (input[j], input[(j + 1) % input.len()]);
+            let (sp, sq) = (side(p), side(q));
+            if sp >= 0.0 {
+                output.push(p);
+            }
+            if (sp >= 0.0) != (sq >= 0.0) {
+                output.push(p + (q - p) * (sp / (sp - sq)));
+            }
+        }
+    }
+    output
+}
+
+// Cyrus–Beck：把线段裁剪到凸多边形（逆时针）里面
+fn clip_segment(a: Vec2, b: Vec2, polygon: &[Vec2]) -> Option<[Vec2; 2]> {
+    let d = b - a;
+    let (mut t0, mut t1) = (0.0f32, 1.0f32);
+    for i in 0..polygon.len() {
+        let (p, q) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+        let normal = (q - p).perp(); // 指向多边形内部
+        let start = normal.dot(a - p);
+        let speed = normal.dot(d);
+        if speed.abs() < f32::EPSILON {
+            if start < 0.0 {
+                return None;
+            }
+            continue;
+        }
+        let t = -start / speed;
+        if speed > 0.0 {
+            t0 = t0.max(t);
+        } else {
+            t1 = t1.min(t);
+        }
+        if t0 >= t1 {
+            return None;
+        }
+    }
+    Some([a + d * t0, a + d * t1])
+}
+
+// 【魔法函数】线性插值
+// 计算阈值 (通常是 0.5) 到底在 p1 和 p2 连线的什么位置
+pub fn interpolate(p1: Vec2, p2: Vec2, v1: f32, v2: f32, iso: f32) -> Vec2 {
+    if (v2 - v1).abs() < 0.0001 {
+        return p1;
+    } // 防止除以0
+    let t = (iso - v1) / (v2 - v1);
+    p1 + (p2 - p1) * t
+}
@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+
+// --- 材质：每个格子除了密度还有一种材质 ---
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[repr(u8)]
+pub enum Material {
+    #[default]
+    Dirt,
+    Stone,
+    Coal,
+    Iron,
+    Gold,
+    Bedrock,
+    Grass, // 地表的草皮，放在最后保证旧存档里的 id 不变
+}
+
+impl Material {
+    pub const ALL: [Material; 7] = [
+        Material::Dirt,
+        Material::Stone,
+        Material::Coal,
+        Material::Iron,
+        Material::Gold,
+        Material::Bedrock,
+        Material::Grass,
+    ];
+
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_id(id: u8) -> Option<Self> {
+        Self::ALL.get(id as usize).copied()
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Material::Dirt => "dirt",
+            Material::Stone => "stone",
+            Material::Coal => "coal",
+            Material::Iron => "iron",
+            Material::Gold => "gold",
+            Material::Bedrock => "bedrock",
+            Material::Grass => "grass",
+        }
+    }
+
+    // 硬度：挖掘量会除以硬度，无穷大表示挖不动
+    pub fn hardness(self) -> f32 {
+        match self {
+            Material::Dirt => 1.0,
+            Material::Stone => 3.0,
+            Material::Coal => 2.5,
+            Material::Iron => 4.0,
+            Material::Gold => 4.0,
+            Material::Bedrock => f32::INFINITY,
+            Material::Grass => 0.8,
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Material::Dirt => Color::srgb(0.45, 0.3, 0.18),
+            Material::Stone => Color::srgb(0.5, 0.5, 0.52),
+            Material::Coal => Color::srgb(0.15, 0.15, 0.16),
+            Material::Iron => Color::srgb(0.72, 0.52, 0.42),
+            Material::Gold => Color::srgb(0.95, 0.8, 0.2),
+            Material::Bedrock => Color::srgb(0.2, 0.18, 0.22),
+            Material::Grass => Color::srgb(0.3, 0.62, 0.22),
+        }
+    }
+}
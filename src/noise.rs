@@ -0,0 +1,120 @@
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
+
+// --- 带种子的 Perlin 噪声和分形叠加（FBM） ---
+
+// 经典 Perlin 噪声：同一个种子在任何平台上都得到同样的结果
+#[derive(Clone)]
+pub struct Perlin {
+    perm: [u8; 512], // 打乱的 0..256，重复两遍省掉取模
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        let mut table: Vec<u8> = (0..=255).collect();
+        table.shuffle(&mut ChaCha8Rng::seed_from_u64(seed));
+        let mut perm = [0; 512];
+        for (i, value) in perm.iter_mut().enumerate() {
+            *value = table[i % 256];
+        }
+        Self { perm }
+    }
+
+    // 大约在 -1..1 之间，整数格点上为 0
+    pub fn get(&self, p: Vec2) -> f32 {
+        let cell = p.floor();
+        let f = p - cell;
+        let (x, y) = (cell.x as i32 & 255, cell.y as i32 & 255);
+
+        let hash = |dx: i32, dy: i32| {
+            let a = self.perm[(x + dx) as usize] as usize;
+            self.perm[a + (y + dy) as usize]
+        };
+        let dot = |h: u8, d: Vec2| GRADIENTS[(h & 7) as usize].dot(d);
+
+        let n00 = dot(hash(0, 0), f);
+        let n10 = dot(hash(1, 0), f - Vec2::X);
+        let n01 = dot(hash(0, 1), f - Vec2::Y);
+        let n11 = dot(hash(1, 1), f - Vec2::ONE);
+
+        let u = fade(f.x);
+        let v = fade(f.y);
+        let bottom = n00 + (n10 - n00) * u;
+        let top = n01 + (n11 - n01) * u;
+        (bottom + (top - bottom) * v) * std::f32::consts::SQRT_2
+    }
+}
+
+const GRADIENTS: [Vec2; 8] = [
+    Vec2::new(1.0, 0.0),
+    Vec2::new(-1.0, 0.0),
+    Vec2::new(0.0, 1.0),
+    Vec2::new(0.0, -1.0),
+    Vec2::new(
+        std::f32::consts::FRAC_1_SQRT_2,
+        std::f32::consts::FRAC_1_SQRT_2,
+    ),
+    Vec2::new(
+        -std::f32::consts::FRAC_1_SQRT_2,
+        std::f32::consts::FRAC_1_SQRT_2,
+    ),
+    Vec2::new(
+        std::f32::consts::FRAC_1_SQRT_2,
+        -std::f32::consts::FRAC_1_SQRT_2,
+    ),
+    Vec2::new(
+        -std::f32::consts::FRAC_1_SQRT_2,
+        -std::f32::consts::FRAC_1_SQRT_2,
+    ),
+];
+
+// 6t^5 - 15t^4 + 10t^3
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+// 分形布朗运动：把不同频率的噪声叠在一起
+#[derive(Clone, Copy, Debug)]
+pub struct Fbm {
+    pub octaves: u32,
+    pub frequency: f32,   // 第一层的频率（每个格子）
+    pub lacunarity: f32,  // 每层频率的倍数
+    pub persistence: f32, // 每层振幅的倍数
+}
+
+impl Default for Fbm {
+    fn default() -> Self {
+        Self {
+            octaves: 4,
+            frequency: 0.05,
+            lacunarity: 2.0,
+            persistence: 0.5,
+        }
+    }
+}
+
+impl Fbm {
+    // 结果按总振幅归一化，大约在 -1..1 之间
+    pub fn sample(&self, noise: &Perlin, p: Vec2) -> f32 {
+        let mut sum = 0.0;
+        let mut total = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = self.frequency;
+        for octave in 0..self.octaves.max(1) {
+            // 每层错开一点，避免各层的格点对齐
+            let offset = Vec2::splat(octave as f32 * 17.31);
+            sum += noise.get(p * frequency + offset) * amplitude;
+            total += amplitude;
+            amplitude *= self.persistence;
+            frequency *= self.lacunarity;
+        }
+        sum / total
+    }
+
+    // 一维噪声：沿着一条水平线取样
+    pub fn sample_1d(&self, noise: &Perlin, x: f32) -> f32 {
+        self.sample(noise, Vec2::new(x, 0.5 / self.frequency))
+    }
+}
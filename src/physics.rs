@@ -0,0 +1,174 @@
+use avian2d::prelude::*;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+
+use crate::brush::cursor_world_pos;
+use crate::contour::extract_contours_in;
+use crate::marching_squares::{self, SaddleResolution};
+use crate::render::{ChunkContourChanged, update_contour_cache};
+use crate::simplify::SimplifyMethod;
+use crate::voxel_map::VoxelMap;
+use crate::{CHUNK_SIZE, ISO_LEVEL, VOXEL_SIZE};
+
+// --- 物理插件：把每个区块的 Marching Squares 结果变成静态碰撞体 ---
+// 只有开启 `physics` feature 时才会编译
+pub struct TerrainPhysicsPlugin;
+
+impl Plugin for TerrainPhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(PhysicsPlugins::default().with_length_unit(VOXEL_SIZE))
+            .insert_resource(Gravity(Vec2::NEG_Y * 50.0 * VOXEL_SIZE))
+            .init_resource::<TerrainColliderMode>()
+            .init_resource::<ChunkColliders>()
+            .add_systems(Update, spawn_test_ball)
+            .add_systems(
+                PostUpdate,
+                rebuild_chunk_colliders.after(update_contour_cache),
+            );
+    }
+}
+
+// 碰撞体的生成方式
+#[derive(Resource, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum TerrainColliderMode {
+    #[default]
+    Polyline, // 沿着轮廓的折线，适合地形表面
+    ConvexDecomposition, // 每个格子的实心凸多边形，整格实心的按行合并成矩形
+}
+
+// 标记碰撞体实体属于哪个区块
+#[derive(Component)]
+pub struct TerrainCollider(pub IVec2);
+
+#[derive(Resource, Default)]
+struct ChunkColliders(HashMap<IVec2, Entity>);
+
+// --- 系统：只有被编辑过的区块才重新生成碰撞体 ---
+fn rebuild_chunk_colliders(
+    mut commands: Commands,
+    mut changed: MessageReader<ChunkContourChanged>,
+    map: Res<VoxelMap>,
+    saddle: Res<SaddleResolution>,
+    mode: Res<TerrainColliderMode>,
+    mut colliders: ResMut<ChunkColliders>,
+) {
+    for ChunkContourChanged(chunk) in changed.read() {
+        let collider = match *mode {
+            TerrainColliderMode::Polyline => polyline_collider(&map, *chunk, *saddle),
+            TerrainColliderMode::ConvexDecomposition => convex_collider(&map, *chunk, *saddle),
+        };
+        match (collider, colliders.0.get(chunk)) {
+            (Some(collider), Some(entity)) => {
+                commands.entity(*entity).insert(collider);
+            }
+            (Some(collider), None) => {
+                let entity = commands
+                    .spawn((
+                        TerrainCollider(*chunk),
+                        RigidBody::Static,
+                        collider,
+                        Transform::default(),
+                    ))
+                    .id();
+                colliders.0.insert(*chunk, entity);
+            }
+            (None, Some(_)) => {
+                if let Some(entity) = colliders.0.remove(chunk) {
+                    commands.entity(entity).despawn();
+                }
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+// 区块内的轮廓（在区块边缘断开），稍微简化后合成一个折线碰撞体
+fn polyline_collider(map: &VoxelMap, chunk: IVec2, saddle: SaddleResolution) -> Option<Collider> {
+    let base = chunk * CHUNK_SIZE;
+    let max = base + IVec2::splat(CHUNK_SIZE - 1);
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    for contour in extract_contours_in(map, base, max, ISO_LEVEL, saddle) {
+        let contour = contour.simplified(VOXEL_SIZE * 0.1, SimplifyMethod::RamerDouglasPeucker);
+        let first = vertices.len() as u32;
+        let n = contour.points.len() as u32;
+        for i in 0..contour.segments().count() as u32 {
+            indices.push([first + i, first + (i + 1) % n]);
+        }
+        vertices.extend(contour.points);
+    }
+
+    (!indices.is_empty()).then(|| Collider::polyline(vertices, Some(indices)))
+}
+
+// 每个格子的实心区域本身就是凸多边形，整格实心的连续格子合并成一个矩形
+fn convex_collider(map: &VoxelMap, chunk: IVec2, saddle: SaddleResolution) -> Option<Collider> {
+    let base = chunk * CHUNK_SIZE;
+    let mut shapes = Vec::new();
+    let rect = |x0: i32, x1: i32, y: i32| {
+        Collider::convex_hull(vec![
+            map.grid_to_world(x0, y),
+            map.grid_to_world(x1, y),
+            map.grid_to_world(x1, y + 1),
+            map.grid_to_world(x0, y + 1),
+        ])
+    };
+
+    for y in base.y..base.y + CHUNK_SIZE {
+        let mut run = None;
+        for x in base.x..base.x + CHUNK_SIZE {
+            let (points, values) = marching_squares::cell_corners(map, x, y);
+            if marching_squares::case_index(values, ISO_LEVEL) == 15 {
+                run.get_or_insert(x);
+                continue;
+            }
+            if let Some(start) = run.take() {
+                shapes.extend(rect(start, x, y));
+            }
+            for polygon in marching_squares::cell_polygons(points, values, ISO_LEVEL, saddle).iter()
+            {
+                shapes.extend(Collider::convex_hull(polygon.to_vec()));
+            }
+        }
+        if let Some(start) = run {
+            shapes.extend(rect(start, base.x + CHUNK_SIZE, y));
+        }
+    }
+
+    (!shapes.is_empty()).then(|| {
+        Collider::compound(
+            shapes
+                .into_iter()
+                .map(|shape| (Position::default(), Rotation::default(), shape))
+                .collect(),
+        )
+    })
+}
+
+// --- 系统：按 B 在鼠标位置丢一个小球，测试碰撞效果 ---
+fn spawn_test_ball(
+    keys: Res<ButtonInput<KeyCode>>,
+    q_window: Query<&Window>,
+    q_camera: Query<(&Camera, &GlobalTransform)>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    if !keys.just_pressed(KeyCode::KeyB) {
+        return;
+    }
+    let Some(world_pos) = cursor_world_pos(&q_window, &q_camera) else {
+        return;
+    };
+
+    let radius = VOXEL_SIZE;
+    commands.spawn((
+        RigidBody::Dynamic,
+        Collider::circle(radius),
+        Restitution::new(0.6),
+        Mesh2d(meshes.add(Circle::new(radius))),
+        MeshMaterial2d(materials.add(Color::srgb(0.9, 0.6, 0.2))),
+        Transform::from_translation(world_pos.extend(1.0)),
+    ));
+}
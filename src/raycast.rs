@@ -0,0 +1,275 @@
+use bevy::prelude::*;
+
+use crate::marching_squares::{self, SaddleResolution};
+use crate::voxel_map::VoxelMap;
+use crate::{ISO_LEVEL, VOXEL_SIZE};
+
+// --- 射线检测：和渲染用同一套 Marching Squares 线段求交，命中点和画出来的墙完全对齐 ---
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+    pub point: Vec2,  // 命中点（世界坐标）
+    pub normal: Vec2, // 表面法线，指向空气一侧
+    pub cell: IVec2,  // 命中的格子（左下角格点的网格坐标）
+    pub distance: f32,
+}
+
+impl VoxelMap {
+    // 世界坐标 -> 连续的网格坐标（格点在整数位置）
+    fn world_to_grid_f32(&self, world_pos: Vec2) -> Vec2 {
+        (world_pos - self.origin()) / VOXEL_SIZE
+    }
+
+    // 双线性插值得到任意位置的密度
+    pub fn sample_density(&self, world_pos: Vec2) -> f32 {
+        let g = self.world_to_grid_f32(world_pos);
+        let cell = g.floor();
+        let (u, v) = (g.x - cell.x, g.y - cell.y);
+        let (x, y) = (cell.x as i32, cell.y as i32);
+        let [v0, v1, v2, v3] = marching_squares::cell_corners(self, x, y).1;
+        v0 * (1.0 - u) * (1.0 - v) + v1 * u * (1.0 - v) + v2 * u * v + v3 * (1.0 - u) * v
+    }
+
+    // 双线性插值的梯度（每世界单位），指向密度变大的方向，也就是实心内部
+    pub fn density_gradient(&self, world_pos: Vec2) -> Vec2 {
+        let g = self.world_to_grid_f32(world_pos);
+        let cell = g.floor();
+        let (u, v) = (g.x - cell.x, g.y - cell.y);
+        let (x, y) = (cell.x as i32, cell.y as i32);
+        let [v0, v1, v2, v3] = marching_squares::cell_corners(self, x, y).1;
+        let du = (1.0 - v) * (v1 - v0) + v * (v2 - v3);
+        let dv = (1.0 - u) * (v3 - v0) + u * (v2 - v1);
+        Vec2::new(du, dv) / VOXEL_SIZE
+    }
+
+    // 点是否落在实心区域里（和填充网格一致）
+    // saddle 要和渲染用的 SaddleResolution 一致，否则鞍点格子里的结果和画出来的墙对不上
+    pub fn is_solid_at(&self, world_pos: Vec2, saddle: SaddleResolution) -> bool {
+        let (x, y) = self.world_to_grid(world_pos);
+        let (points, values) = marching_squares::cell_corners(self, x, y);
+        marching_squares::cell_polygons(points, values, ISO_LEVEL, saddle)
+            .iter()
+            .any(|polygon| {
+                (0..polygon.len()).all(|i| {
+                    let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+                    (b - a).perp_dot(world_pos - a) >= 0.0
+                })
+            })
+    }
+
+    // 从 origin 沿 dir 发射射线，返回 max_dist 以内第一次碰到墙的位置
+    pub fn raycast(
+        &self,
+        origin: Vec2,
+        dir: Vec2,
+        max_dist: f32,
+        saddle: SaddleResolution,
+    ) -> Option<RayHit> {
+        let dir = dir.try_normalize()?;
+        if self.is_solid_at(origin, saddle) {
+            let (x, y) = self.world_to_grid(origin);
+            return Some(self.hit_at(origin, IVec2::new(x, y), 0.0, None));
+        }
+
+        let (t_start, t_end) = self.ray_range(origin, dir, 0.0, max_dist)?;
+
+        // DDA：按射线穿过的顺序逐个访问格子
+        let start = self.world_to_grid_f32(origin + dir * t_start);
+        let mut cell = start.floor().as_ivec2();
+        let step = IVec2::new(dir.x.signum() as i32, dir.y.signum() as i32);
+        let t_delta = Vec2::new(
+            VOXEL_SIZE / dir.x.abs().max(f32::EPSILON),
+            VOXEL_SIZE / dir.y.abs().max(f32::EPSILON),
+        );
+        // 到下一条竖直/水平网格线还要走多远
+        let frac = start - start.floor();
+        let to_next = Vec2::new(
+            if step.x > 0 { 1.0 - frac.x } else { frac.x },
+            if step.y > 0 { 1.0 - frac.y } else { frac.y },
+        );
+        let mut t_max = Vec2::splat(t_start) + to_next * t_delta;
+
+        let mut t = t_start;
+        while t <= t_end {
+            if let Some((distance, segment)) = self.cast_in_cell(cell, origin, dir, saddle) {
+                if distance > t_end {
+                    return None;
+                }
+                let point = origin + dir * distance;
+                return Some(self.hit_at(point, cell, distance, Some(segment)));
+            }
+            if t_max.x < t_max.y {
+                t = t_max.x;
+                t_max.x += t_delta.x;
+                cell.x += step.x;
+            } else {
+                t = t_max.y;
+                t_max.y += t_delta.y;
+                cell.y += step.y;
+            }
+        }
+        None
+    }
+
+    // 圆形扫掠：半径为 radius 的圆沿射线移动，返回第一次接触墙的位置
+    // 命中点是墙上的接触点，法线从接触点指向圆心
+    pub fn circle_cast(
+        &self,
+        origin: Vec2,
+        dir: Vec2,
+        radius: f32,
+        max_dist: f32,
+        saddle: SaddleResolution,
+    ) -> Option<RayHit> {
+        let dir = dir.try_normalize()?;
+        if radius <= 0.0 {
+            return self.raycast(origin, dir, max_dist, saddle);
+        }
+        let touches = |t: f32| self.closest_surface(origin + dir * t, radius, saddle);
+        if let Some((contact, cell)) = touches(0.0) {
+            return Some(self.circle_hit(origin, contact, cell, 0.0));
+        }
+
+        // 只在有区块的范围（再加上半径）里走，max_dist 是无穷大也能停下来
+        let (t_start, t_end) = self.ray_range(origin, dir, radius, max_dist)?;
+
+        // 步长不超过半径的一半，保证不会直接穿过薄墙
+        let step = radius.min(VOXEL_SIZE) * 0.5;
+        let mut prev = t_start;
+        while prev < t_end {
+            let t = (prev + step).min(t_end);
+            if touches(t).is_some() {
+                // 二分找到刚好接触的位置
+                let (mut lo, mut hi) = (prev, t);
+                for _ in 0..12 {
+                    let mid = (lo + hi) / 2.0;
+                    if touches(mid).is_some() {
+                        hi = mid;
+                    } else {
+                        lo = mid;
+                    }
+                }
+                let center = origin + dir * hi;
+                let (contact, cell) = touches(hi)?;
+                return Some(self.circle_hit(center, contact, cell, hi));
+            }
+            prev = t;
+        }
+        None
+    }
+
+    // 射线落在有区块的范围（向外扩 margin）里的那一段 [t_start, t_end]，外面都是空气
+    fn ray_range(&self, origin: Vec2, dir: Vec2, margin: f32, max_dist: f32) -> Option<(f32, f32)> {
+        let (min, max) = self.bounds()?;
+        let box_min = self.grid_to_world(min.x - 1, min.y - 1) - Vec2::splat(margin);
+        let box_max = self.grid_to_world(max.x + 1, max.y + 1) + Vec2::splat(margin);
+        let (t_enter, t_exit) = ray_box(origin, dir, box_min, box_max)?;
+        let t_start = t_enter.max(0.0);
+        let t_end = t_exit.min(max_dist);
+        (t_start <= t_end).then_some((t_start, t_end))
+    }
+
+    fn hit_at(
+        &self,
+        point: Vec2,
+        cell: IVec2,
+        distance: f32,
+        segment: Option<[Vec2; 2]>,
+    ) -> RayHit {
+        // 法线来自密度梯度，梯度太小（比如在实心内部）时退回到线段的法线
+        let normal = (-self.density_gradient(point))
+            .try_normalize()
+            .or_else(|| segment.and_then(|[a, b]| (a - b).perp().try_normalize()))
+            .unwrap_or(Vec2::Y);
+        RayHit {
+            point,
+            normal,
+            cell,
+            distance,
+        }
+    }
+
+    fn circle_hit(&self, center: Vec2, contact: Vec2, cell: IVec2, distance: f32) -> RayHit {
+        let normal = (center - contact)
+            .try_normalize()
+            .or_else(|| (-self.density_gradient(contact)).try_normalize())
+            .unwrap_or(Vec2::Y);
+        RayHit {
+            point: contact,
+            normal,
+            cell,
+            distance,
+        }
+    }
+
+    // 射线和一个格子里的线段求交，只算从空气进入实心的那一侧
+    fn cast_in_cell(
+        &self,
+        cell: IVec2,
+        origin: Vec2,
+        dir: Vec2,
+        saddle: SaddleResolution,
+    ) -> Option<(f32, [Vec2; 2])> {
+        let (points, values) = marching_squares::cell_corners(self, cell.x, cell.y);
+        marching_squares::march_cell(points, values, ISO_LEVEL, saddle)
+            .iter()
+            .filter(|[a, b]| dir.dot((*b - *a).perp()) > 0.0)
+            .filter_map(|segment| ray_segment(origin, dir, segment).map(|t| (t, segment)))
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+    }
+
+    // 圆心 center 半径 radius 范围内最近的墙面点，圆心在实心里时返回圆心
+    fn closest_surface(
+        &self,
+        center: Vec2,
+        radius: f32,
+        saddle: SaddleResolution,
+    ) -> Option<(Vec2, IVec2)> {
+        let (cx, cy) = self.world_to_grid(center);
+        if self.is_solid_at(center, saddle) {
+            return Some((center, IVec2::new(cx, cy)));
+        }
+        let reach = (radius / VOXEL_SIZE).ceil() as i32;
+        let mut best: Option<(f32, Vec2, IVec2)> = None;
+        for y in cy - reach..=cy + reach {
+            for x in cx - reach..=cx + reach {
+                let (points, values) = marching_squares::cell_corners(self, x, y);
+                let segments = marching_squares::march_cell(points, values, ISO_LEVEL, saddle);
+                for [a, b] in segments.iter() {
+                    let ab = b - a;
+                    let t = ((center - a).dot(ab) / ab.length_squared().max(f32::EPSILON))
+                        .clamp(0.0, 1.0);
+                    let point = a + ab * t;
+                    let dist = point.distance(center);
+                    if dist <= radius && best.is_none_or(|(d, _, _)| dist < d) {
+                        best = Some((dist, point, IVec2::new(x, y)));
+                    }
+                }
+            }
+        }
+        best.map(|(_, point, cell)| (point, cell))
+    }
+}
+
+// 射线和线段求交，返回沿射线的距离
+fn ray_segment(origin: Vec2, dir: Vec2, [a, b]: [Vec2; 2]) -> Option<f32> {
+    let ab = b - a;
+    let denom = dir.perp_dot(ab);
+    if denom.abs() < f32::EPSILON {
+        return None; // 平行
+    }
+    let ao = a - origin;
+    let t = ao.perp_dot(ab) / denom;
+    let s = ao.perp_dot(dir) / denom;
+    (t >= 0.0 && (0.0..=1.0).contains(&s)).then_some(t)
+}
+
+// 射线和轴对齐包围盒求交，返回 (进入距离, 离开距离)
+fn ray_box(origin: Vec2, dir: Vec2, min: Vec2, max: Vec2) -> Option<(f32, f32)> {
+    let inv = dir.recip();
+    let t0 = (min - origin) * inv;
+    let t1 = (max - origin) * inv;
+    let t_enter = t0.min(t1).max_element();
+    let t_exit = t0.max(t1).min_element();
+    (t_exit >= t_enter.max(0.0)).then_some((t_enter, t_exit))
+}
@@ -0,0 +1,278 @@
+use bevy::asset::RenderAssetUsages;
+use bevy::camera::CameraUpdateSystems;
+use bevy::camera::primitives::Aabb;
+use bevy::camera::visibility::VisibilitySystems;
+use bevy::mesh::PrimitiveTopology;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+
+use crate::camera::visible_world_rect;
+use crate::marching_squares::{self, CellSegments, MaterialCell, SaddleResolution};
+use crate::material::Material;
+use crate::voxel_map::VoxelMap;
+use crate::{CHUNK_SIZE, ISO_LEVEL, VOXEL_SIZE};
+
+// --- 地形渲染插件：只在地图被修改的地方重新计算轮廓和网格 ---
+pub struct TerrainRenderPlugin;
+
+impl Plugin for TerrainRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ContourCache>()
+            .init_resource::<ChunkMeshes>()
+            .init_resource::<SaddleResolution>()
+            .add_message::<ChunkContourChanged>()
+            .add_systems(Startup, setup_terrain_material)
+            .add_systems(
+                PostUpdate,
+                (
+                    toggle_saddle_resolution,
+                    update_contour_cache,
+                    // 换掉的网格要在 Bevy 重新计算包围盒之前处理好
+                    sync_chunk_meshes.before(VisibilitySystems::CalculateBounds),
+                    // 等相机的变换和投影更新完再算可见范围
+                    draw_marching_squares.after(CameraUpdateSystems),
+                )
+                    .chain(),
+            );
+    }
+}
+
+// 某个区块的轮廓缓存刚刚被重算过
+#[derive(Message)]
+pub struct ChunkContourChanged(pub IVec2);
+
+// 一个区块里所有格子的轮廓缓存
+// 格子归属于它左下角所在的区块
+struct ChunkContour {
+    cells: Vec<CellSegments>,
+    fills: Vec<MaterialCell>,
+    samples: Vec<f32>,                     // 每个格子左下角的密度，用来画调试点
+    segments: Vec<[Vec2; 2]>,              // 扁平化后的线段，绘制时直接使用
+    boundaries: Vec<[Vec2; 2]>,            // 扁平化后的材质分界线
+    triangles: Vec<([Vec2; 3], Material)>, // 扁平化后的三角形，用来生成网格
+    sample_points: Vec<(Vec2, f32)>,       // 扁平化后的调试点
+}
+
+impl ChunkContour {
+    fn new() -> Self {
+        let len = (CHUNK_SIZE * CHUNK_SIZE) as usize;
+        Self {
+            cells: vec![CellSegments::default(); len],
+            fills: (0..len).map(|_| MaterialCell::default()).collect(),
+            samples: vec![0.0; len],
+            segments: Vec::new(),
+            boundaries: Vec::new(),
+            triangles: Vec::new(),
+            sample_points: Vec::new(),
+        }
+    }
+
+    // 局部重算完成后，重新整理出扁平列表
+    fn rebuild(&mut self, map: &VoxelMap, chunk: IVec2) {
+        self.segments.clear();
+        self.boundaries.clear();
+        self.triangles.clear();
+        self.sample_points.clear();
+        let base = chunk * CHUNK_SIZE;
+        for (i, (cell, fill)) in self.cells.iter().zip(&self.fills).enumerate() {
+            self.segments.extend(cell.iter());
+            self.boundaries.extend(&fill.boundaries);
+            self.triangles.extend(&fill.triangles);
+            let density = self.samples[i];
+            if density > 0.0 {
+                let x = base.x + i as i32 % CHUNK_SIZE;
+                let y = base.y + i as i32 / CHUNK_SIZE;
+                self.sample_points.push((map.grid_to_world(x, y), density));
+            }
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.segments.is_empty() && self.triangles.is_empty() && self.sample_points.is_empty()
+    }
+
+    // 把三角形写进一个 Bevy Mesh：位置、法线、UV（按区块大小平铺）、材质颜色
+    fn build_mesh(&self, origin: Vec2) -> Mesh {
+        let uv_scale = 1.0 / (VOXEL_SIZE * CHUNK_SIZE as f32);
+        let vertices = || {
+            self.triangles
+                .iter()
+                .flat_map(|(triangle, material)| triangle.iter().map(move |p| (*p, *material)))
+        };
+        let positions: Vec<[f32; 3]> = vertices().map(|(p, _)| [p.x, p.y, 0.0]).collect();
+        let colors: Vec<[f32; 4]> = vertices()
+            .map(|(_, material)| material.color().to_linear().to_f32_array())
+            .collect();
+        let uvs: Vec<[f32; 2]> = vertices()
+            .map(|(p, _)| {
+                let uv = (p - origin) * uv_scale;
+                [uv.x, 1.0 - uv.y] // 纹理坐标的 v 轴朝下
+            })
+            .collect();
+        let normals = vec![[0.0, 0.0, 1.0]; positions.len()];
+
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ContourCache {
+    chunks: HashMap<IVec2, ChunkContour>,
+}
+
+// 地形填充使用的材质
+#[derive(Resource)]
+pub struct TerrainMaterial(pub Handle<ColorMaterial>);
+
+// 每个区块对应的网格实体
+#[derive(Resource, Default)]
+struct ChunkMeshes(HashMap<IVec2, (Entity, Handle<Mesh>)>);
+
+// 标记地形网格实体属于哪个区块
+#[derive(Component)]
+pub struct TerrainChunk(pub IVec2);
+
+fn setup_terrain_material(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>) {
+    let material = materials.add(Color::WHITE); // 颜色来自顶点上的材质颜色
+    commands.insert_resource(TerrainMaterial(material));
+}
+
+// --- 系统：按 Tab 切换鞍点的处理方式，切换后整张地图重算 ---
+fn toggle_saddle_resolution(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut saddle: ResMut<SaddleResolution>,
+    mut map: ResMut<VoxelMap>,
+) {
+    if keys.just_pressed(KeyCode::Tab) {
+        *saddle = saddle.next();
+        info!("saddle resolution: {:?}", *saddle);
+        map.mark_all_dirty();
+    }
+}
+
+// --- 系统：把脏区域（加一圈边框）重新跑一遍 Marching Squares ---
+pub fn update_contour_cache(
+    mut map: ResMut<VoxelMap>,
+    saddle: Res<SaddleResolution>,
+    mut cache: ResMut<ContourCache>,
+    mut changed: MessageWriter<ChunkContourChanged>,
+) {
+    if !map.has_dirty() {
+        return;
+    }
+    let dirty = map.take_dirty();
+
+    for (chunk, (min, max)) in dirty {
+        let contour = cache.chunks.entry(chunk).or_insert_with(ChunkContour::new);
+        let base = chunk * CHUNK_SIZE;
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let (points, values) = marching_squares::cell_corners(&map, x, y);
+                let idx = ((y - base.y) * CHUNK_SIZE + (x - base.x)) as usize;
+                contour.cells[idx] =
+                    marching_squares::march_cell(points, values, ISO_LEVEL, *saddle);
+                let materials = marching_squares::cell_materials(&map, x, y);
+                contour.fills[idx] =
+                    marching_squares::material_cell(points, values, materials, ISO_LEVEL, *saddle);
+                contour.samples[idx] = values[0];
+            }
+        }
+        contour.rebuild(&map, chunk);
+        if contour.is_empty() {
+            cache.chunks.remove(&chunk);
+        }
+        changed.write(ChunkContourChanged(chunk));
+    }
+}
+
+// --- 系统：把重算过的区块写回 Mesh2d 实体 ---
+fn sync_chunk_meshes(
+    mut commands: Commands,
+    mut changed: MessageReader<ChunkContourChanged>,
+    map: Res<VoxelMap>,
+    cache: Res<ContourCache>,
+    material: Res<TerrainMaterial>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut chunk_meshes: ResMut<ChunkMeshes>,
+) {
+    for ChunkContourChanged(chunk) in changed.read() {
+        let contour = cache.chunks.get(chunk).filter(|c| !c.triangles.is_empty());
+        match (contour, chunk_meshes.0.get(chunk)) {
+            (Some(contour), Some((entity, handle))) => {
+                if let Some(mesh) = meshes.get_mut(handle) {
+                    *mesh = contour.build_mesh(map.origin());
+                    // Bevy 只给没有 Aabb 的 Mesh2d 计算包围盒，网格变了要删掉旧的，
+                    // 否则新填的部分落在旧包围盒外面会被视锥剔除
+                    commands.entity(*entity).remove::<Aabb>();
+                }
+            }
+            (Some(contour), None) => {
+                let handle = meshes.add(contour.build_mesh(map.origin()));
+                let entity = commands
+                    .spawn((
+                        TerrainChunk(*chunk),
+                        Mesh2d(handle.clone()),
+                        MeshMaterial2d(material.0.clone()),
+                    ))
+                    .id();
+                chunk_meshes.0.insert(*chunk, (entity, handle));
+            }
+            (None, Some(_)) => {
+                if let Some((entity, handle)) = chunk_meshes.0.remove(chunk) {
+                    commands.entity(entity).despawn();
+                    meshes.remove(&handle);
+                }
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+// --- 核心系统：Marching Squares 可视化 ---
+// 填充交给网格，这里只在上面叠加轮廓线和调试点
+// 只画和相机可见范围相交的区块；轮廓缓存本身仍然整张地图更新，物理碰撞体要用
+fn draw_marching_squares(
+    map: Res<VoxelMap>,
+    cache: Res<ContourCache>,
+    q_camera: Query<(&Camera, &GlobalTransform)>,
+    mut gizmos: Gizmos,
+) {
+    let color = Color::srgb(0.0, 1.0, 0.0); // 绿色墙壁线
+    let boundary_color = Color::srgba(1.0, 1.0, 1.0, 0.4); // 材质分界线
+
+    // 没有相机时不剔除
+    let visible = q_camera
+        .iter()
+        .find(|(camera, _)| camera.is_active)
+        .and_then(|(camera, transform)| visible_world_rect(camera, transform));
+
+    for (chunk, contour) in &cache.chunks {
+        if let Some(visible) = visible {
+            // 区块里的格子一直延伸到下一个区块的第一排格点，调试点还有半径，多留一格
+            let base = *chunk * CHUNK_SIZE;
+            let min = map.grid_to_world(base.x, base.y);
+            let max = map.grid_to_world(base.x + CHUNK_SIZE, base.y + CHUNK_SIZE);
+            let bounds = Rect::from_corners(min, max).inflate(VOXEL_SIZE);
+            if bounds.intersect(visible).is_empty() {
+                continue;
+            }
+        }
+        // 调试显示：画出原始数据点（红色小点）
+        for &(point, density) in &contour.sample_points {
+            gizmos.circle_2d(point, 1.0 + density * 2.0, Color::srgba(1.0, 0.0, 0.0, 0.3));
+        }
+        for &[start, end] in &contour.segments {
+            gizmos.line_2d(start, end, color);
+        }
+        for &[start, end] in &contour.boundaries {
+            gizmos.line_2d(start, end, boundary_color);
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use bevy::prelude::*;
+use flate2::Compression;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+
+use crate::CHUNK_SIZE;
+use crate::history::EditHistory;
+use crate::material::Material;
+use crate::voxel_map::VoxelMap;
+
+// --- 存档：把 VoxelMap 写成紧凑的二进制文件 ---
+//
+// 文件布局（小端序）：
+//   magic      4 字节 "VX2D"
+//   version    u16
+//   flags      u8   bit0 = 带材质层，bit1 = 数据段经过 zlib 压缩
+//   quant      u8   密度的量化方式，见 Quantization
+//   chunk_size u16
+//   origin     f32 x 2
+//   bounds     i32 x 4  所有区块覆盖的网格范围 (min, max)，max 不包含
+//   count      u32  区块数量
+//   table      count 个 (i32, i32) 区块坐标
+//   数据段     按 table 的顺序，每个区块先是 chunk_size² 个密度，再是 chunk_size² 个材质 id
+
+pub const MAGIC: [u8; 4] = *b"VX2D";
+pub const VERSION: u16 = 1;
+
+const FLAG_MATERIALS: u8 = 1 << 0;
+const FLAG_COMPRESSED: u8 = 1 << 1;
+
+// 密度的存储精度
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum Quantization {
+    U8,
+    #[default]
+    U16,
+    F32, // 不丢精度
+}
+
+impl Quantization {
+    fn id(self) -> u8 {
+        match self {
+            Self::U8 => 0,
+            Self::U16 => 1,
+            Self::F32 => 2,
+        }
+    }
+
+    fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Self::U8),
+            1 => Some(Self::U16),
+            2 => Some(Self::F32),
+            _ => None,
+        }
+    }
+
+    fn write(self, out: &mut Vec<u8>, value: f32) {
+        match self {
+            Self::U8 => out.push((value * u8::MAX as f32).round() as u8),
+            Self::U16 => {
+                let q = (value * u16::MAX as f32).round() as u16;
+                out.extend_from_slice(&q.to_le_bytes());
+            }
+            Self::F32 => out.extend_from_slice(&value.to_le_bytes()),
+        }
+    }
+
+    fn read(self, input: &mut impl Read) -> io::Result<f32> {
+        Ok(match self {
+            Self::U8 => read_u8(input)? as f32 / u8::MAX as f32,
+            Self::U16 => read_u16(input)? as f32 / u16::MAX as f32,
+            Self::F32 => read_f32(input)?,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SaveOptions {
+    pub quantization: Quantization,
+    pub materials: bool, // 不写材质层时读回来全是默认材质
+    pub compress: bool,
+}
+
+impl Default for SaveOptions {
+    fn default() -> Self {
+        Self {
+            quantization: Quantization::default(),
+            materials: true,
+            compress: true,
+        }
+    }
+}
+
+impl VoxelMap {
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        self.write_to(&mut file, SaveOptions::default())?;
+        file.flush()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+
+    pub fn write_to(&self, mut out: impl Write, options: SaveOptions) -> io::Result<()> {
+        // 区块按坐标排序，同一张地图每次写出的字节都一样
+        let mut chunks: Vec<_> = self.chunks().collect();
+        chunks.sort_by_key(|(key, _)| (key.y, key.x));
+        let (min, max) = self.bounds().unwrap_or_default();
+
+        let mut flags = 0;
+        if options.materials {
+            flags |= FLAG_MATERIALS;
+        }
+        if options.compress {
+            flags |= FLAG_COMPRESSED;
+        }
+
+        out.write_all(&MAGIC)?;
+        out.write_all(&VERSION.to_le_bytes())?;
+        out.write_all(&[flags, options.quantization.id()])?;
+        out.write_all(&(CHUNK_SIZE as u16).to_le_bytes())?;
+        for v in [self.origin().x, self.origin().y] {
+            out.write_all(&v.to_le_bytes())?;
+        }
+        for v in [min.x, min.y, max.x, max.y] {
+            out.write_all(&v.to_le_bytes())?;
+        }
+        out.write_all(&(chunks.len() as u32).to_le_bytes())?;
+        for (key, _) in &chunks {
+            out.write_all(&key.x.to_le_bytes())?;
+            out.write_all(&key.y.to_le_bytes())?;
+        }
+
+        let mut data = Vec::new();
+        for (_, chunk) in &chunks {
+            for local in chunk_cells() {
+                options.quantization.write(&mut data, chunk.get(local));
+            }
+            if options.materials {
+                data.extend(chunk_cells().map(|local| chunk.material(local).id()));
+            }
+        }
+
+        if options.compress {
+            let mut encoder = ZlibEncoder::new(out, Compression::default());
+            encoder.write_all(&data)?;
+            encoder.finish()?;
+        } else {
+            out.write_all(&data)?;
+        }
+        Ok(())
+    }
+
+    pub fn read_from(mut input: impl Read) -> io::Result<Self> {
+        let mut magic = [0; 4];
+        input.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(invalid("not a voxel map file"));
+        }
+        let version = read_u16(&mut input)?;
+        if version != VERSION {
+            return Err(invalid(format!("unsupported version {version}")));
+        }
+        let flags = read_u8(&mut input)?;
+        let quantization = Quantization::from_id(read_u8(&mut input)?)
+            .ok_or_else(|| invalid("bad quantization"))?;
+        if read_u16(&mut input)? as i32 != CHUNK_SIZE {
+            return Err(invalid("chunk size mismatch"));
+        }
+        let origin = Vec2::new(read_f32(&mut input)?, read_f32(&mut input)?);
+        // 范围只是给外部工具看的，读的时候以区块表为准
+        for _ in 0..4 {
+            read_i32(&mut input)?;
+        }
+        let count = read_u32(&mut input)?;
+        let mut keys = Vec::new();
+        for _ in 0..count {
+            keys.push(IVec2::new(read_i32(&mut input)?, read_i32(&mut input)?));
+        }
+
+        let mut data: Box<dyn Read> = if flags & FLAG_COMPRESSED != 0 {
+            Box::new(ZlibDecoder::new(input))
+        } else {
+            Box::new(input)
+        };
+
+        let mut map = VoxelMap::empty(origin);
+        for key in keys {
+            let base = key * CHUNK_SIZE;
+            // 先写密度再写材质：区块在写入第一个实心格子时才会被创建
+            for local in chunk_cells() {
+                let density = quantization.read(&mut data)?;
+                map.set_density(base.x + local.x, base.y + local.y, density);
+            }
+            if flags & FLAG_MATERIALS != 0 {
+                for local in chunk_cells() {
+                    let material = Material::from_id(read_u8(&mut data)?)
+                        .ok_or_else(|| invalid("bad material id"))?;
+                    map.set_material(base.x + local.x, base.y + local.y, material);
+                }
+            }
+        }
+        Ok(map)
+    }
+}
+
+// 区块内所有格子的局部坐标，按行优先
+fn chunk_cells() -> impl Iterator<Item = IVec2> {
+    (0..CHUNK_SIZE).flat_map(|y| (0..CHUNK_SIZE).map(move |x| IVec2::new(x, y)))
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+fn read_u8(input: &mut impl Read) -> io::Result<u8> {
+    let mut buf = [0; 1];
+    input.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u16(input: &mut impl Read) -> io::Result<u16> {
+    let mut buf = [0; 2];
+    input.read_exact(&mut buf)?;
+    Ok(u16::from_le_bytes(buf))
+}
+
+fn read_u32(input: &mut impl Read) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    input.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_i32(input: &mut impl Read) -> io::Result<i32> {
+    let mut buf = [0; 4];
+    input.read_exact(&mut buf)?;
+    Ok(i32::from_le_bytes(buf))
+}
+
+fn read_f32(input: &mut impl Read) -> io::Result<f32> {
+    let mut buf = [0; 4];
+    input.read_exact(&mut buf)?;
+    Ok(f32::from_le_bytes(buf))
+}
+
+// --- 存档插件：F5 保存，F9 读取 ---
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SavePath>()
+            .add_systems(Update, save_load_keys);
+    }
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct SavePath(pub String);
+
+impl Default for SavePath {
+    fn default() -> Self {
+        Self("terrain.vx2d".to_string())
+    }
+}
+
+fn save_load_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    path: Res<SavePath>,
+    mut map: ResMut<VoxelMap>,
+    mut history: Option<ResMut<EditHistory>>,
+) {
+    if keys.just_pressed(KeyCode::F5) {
+        match map.save(&path.0) {
+            Ok(()) => info!("saved {} chunks to {}", map.chunk_count(), path.0),
+            Err(err) => error!("failed to save {}: {err}", path.0),
+        }
+    }
+    if keys.just_pressed(KeyCode::F9) {
+        match VoxelMap::load(&path.0) {
+            Ok(loaded) => {
+                map.replace(loaded);
+                if let Some(history) = history.as_mut() {
+                    history.clear();
+                }
+                info!("loaded {} chunks from {}", map.chunk_count(), path.0);
+            }
+            Err(err) => error!("failed to load {}: {err}", path.0),
+        }
+    }
+}
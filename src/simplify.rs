@@ -0,0 +1,260 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use bevy::prelude::*;
+
+use crate::contour::Contour;
+
+// --- 轮廓简化：去掉平直墙面上几乎共线的插值点 ---
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum SimplifyMethod {
+    #[default]
+    RamerDouglasPeucker, // 偏离弦的距离小于容差的点被去掉
+    Visvalingam, // 与相邻两点组成的三角形面积小于容差平方的点被去掉
+}
+
+impl Contour {
+    // 按世界单位的容差简化，闭合的环至少保留 3 个点，并且保证简化后不会自相交
+    pub fn simplified(&self, tolerance: f32, method: SimplifyMethod) -> Contour {
+        let min_len = if self.closed { 3 } else { 2 };
+        let mut tolerance = tolerance;
+        // 简化后如果自相交，就减小容差重试，实在不行保留原样
+        for _ in 0..8 {
+            let points = match method {
+                SimplifyMethod::RamerDouglasPeucker => {
+                    ramer_douglas_peucker(&self.points, self.closed, tolerance)
+                }
+                SimplifyMethod::Visvalingam => visvalingam(&self.points, self.closed, tolerance),
+            };
+            if points.len() >= min_len && !self_intersects(&points, self.closed) {
+                return Contour {
+                    points,
+                    closed: self.closed,
+                };
+            }
+            tolerance *= 0.5;
+        }
+        self.clone()
+    }
+}
+
+// 一次简化一整组轮廓
+pub fn simplify_contours(
+    contours: &[Contour],
+    tolerance: f32,
+    method: SimplifyMethod,
+) -> Vec<Contour> {
+    contours
+        .iter()
+        .map(|c| c.simplified(tolerance, method))
+        .collect()
+}
+
+// Ramer–Douglas–Peucker
+// 闭合的环先在第一个点和离它最远的点处切成两段，分别简化后再拼回去
+pub fn ramer_douglas_peucker(points: &[Vec2], closed: bool, tolerance: f32) -> Vec<Vec2> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+    let mut keep = vec![false; points.len()];
+
+    if closed {
+        let far = (1..points.len())
+            .max_by(|&a, &b| {
+                let da = points[a].distance_squared(points[0]);
+                let db = points[b].distance_squared(points[0]);
+                da.total_cmp(&db)
+            })
+            .unwrap_or(0);
+        keep[0] = true;
+        keep[far] = true;
+        rdp_range(points, 0, far, tolerance, &mut keep);
+        // 后半段从 far 走回第一个点，把它展开成连续的数组
+        let tail: Vec<Vec2> = points[far..].iter().copied().chain([points[0]]).collect();
+        let mut tail_keep = vec![false; tail.len()];
+        rdp_range(&tail, 0, tail.len() - 1, tolerance, &mut tail_keep);
+        for (i, k) in tail_keep.iter().enumerate().take(tail.len() - 1) {
+            keep[far + i] |= *k;
+        }
+    } else {
+        keep[0] = true;
+        keep[points.len() - 1] = true;
+        rdp_range(points, 0, points.len() - 1, tolerance, &mut keep);
+    }
+
+    points
+        .iter()
+        .zip(&keep)
+        .filter_map(|(p, k)| k.then_some(*p))
+        .collect()
+}
+
+fn rdp_range(points: &[Vec2], first: usize, last: usize, tolerance: f32, keep: &mut [bool]) {
+    if last <= first + 1 {
+        return;
+    }
+    let (index, dist) = (first + 1..last)
+        .map(|i| {
+            (
+                i,
+                distance_to_segment(points[i], points[first], points[last]),
+            )
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or((first, 0.0));
+    if dist > tolerance {
+        keep[index] = true;
+        rdp_range(points, first, index, tolerance, keep);
+        rdp_range(points, index, last, tolerance, keep);
+    }
+}
+
+// Visvalingam–Whyatt
+// 每次去掉“有效面积”最小的点，直到最小面积超过 tolerance²
+pub fn visvalingam(points: &[Vec2], closed: bool, tolerance: f32) -> Vec<Vec2> {
+    let n = points.len();
+    let min_len = if closed { 3 } else { 2 };
+    if n <= min_len {
+        return points.to_vec();
+    }
+    let threshold = tolerance * tolerance;
+
+    // 用双向链表记录还剩下哪些点
+    let mut prev: Vec<usize> = (0..n).map(|i| (i + n - 1) % n).collect();
+    let mut next: Vec<usize> = (0..n).map(|i| (i + 1) % n).collect();
+    let mut removed = vec![false; n];
+    let mut version = vec![0u32; n];
+    let removable = |i: usize| closed || (i != 0 && i != n - 1);
+
+    let area = |i: usize, prev: &[usize], next: &[usize]| {
+        triangle_area(points[prev[i]], points[i], points[next[i]])
+    };
+
+    let mut heap = BinaryHeap::new();
+    for i in (0..n).filter(|&i| removable(i)) {
+        heap.push(Candidate {
+            area: area(i, &prev, &next),
+            index: i,
+            version: 0,
+        });
+    }
+
+    let mut remaining = n;
+    while let Some(candidate) = heap.pop() {
+        if removed[candidate.index] || candidate.version != version[candidate.index] {
+            continue; // 过期的记录
+        }
+        if candidate.area >= threshold || remaining <= min_len {
+            break;
+        }
+        let i = candidate.index;
+        removed[i] = true;
+        remaining -= 1;
+        let (p, q) = (prev[i], next[i]);
+        next[p] = q;
+        prev[q] = p;
+        // 两个邻居的面积变了，重新入堆
+        for j in [p, q] {
+            if removable(j) {
+                version[j] += 1;
+                heap.push(Candidate {
+                    area: area(j, &prev, &next),
+                    index: j,
+                    version: version[j],
+                });
+            }
+        }
+    }
+
+    points
+        .iter()
+        .zip(&removed)
+        .filter_map(|(p, r)| (!r).then_some(*p))
+        .collect()
+}
+
+// 堆里按面积从小到大排
+struct Candidate {
+    area: f32,
+    index: usize,
+    version: u32,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .area
+            .total_cmp(&self.area)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+fn triangle_area(a: Vec2, b: Vec2, c: Vec2) -> f32 {
+    (b - a).perp_dot(c - a).abs() / 2.0
+}
+
+fn distance_to_segment(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    let len_sq = ab.length_squared();
+    if len_sq < f32::EPSILON {
+        return p.distance(a);
+    }
+    let t = ((p - a).dot(ab) / len_sq).clamp(0.0, 1.0);
+    p.distance(a + ab * t)
+}
+
+// 检查折线自身有没有不相邻的两段相交
+pub fn self_intersects(points: &[Vec2], closed: bool) -> bool {
+    let n = points.len();
+    let count = if closed { n } else { n.saturating_sub(1) };
+    for i in 0..count {
+        for j in i + 1..count {
+            // 相邻的两段共用一个端点，不算相交
+            let adjacent = j == i + 1 || (closed && i == 0 && j == count - 1);
+            if adjacent {
+                continue;
+            }
+            let (a, b) = (points[i], points[(i + 1) % n]);
+            let (c, d) = (points[j], points[(j + 1) % n]);
+            if segments_intersect(a, b, c, d) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+fn segments_intersect(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> bool {
+    let d1 = (b - a).perp_dot(c - a);
+    let d2 = (b - a).perp_dot(d - a);
+    let d3 = (d - c).perp_dot(a - c);
+    let d4 = (d - c).perp_dot(b - c);
+    if ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+        && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
+    {
+        return true;
+    }
+    // 共线并且重叠的情况
+    let on_segment = |p: Vec2, q: Vec2, r: Vec2| {
+        r.x >= p.x.min(q.x) && r.x <= p.x.max(q.x) && r.y >= p.y.min(q.y) && r.y <= p.y.max(q.y)
+    };
+    (d1 == 0.0 && on_segment(a, b, c))
+        || (d2 == 0.0 && on_segment(a, b, d))
+        || (d3 == 0.0 && on_segment(c, d, a))
+        || (d4 == 0.0 && on_segment(c, d, b))
+}
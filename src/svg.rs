@@ -0,0 +1,148 @@
+use std::fmt::Write as _;
+use std::io;
+use std::path::Path;
+
+use bevy::prelude::*;
+
+use crate::ISO_LEVEL;
+use crate::contour::{Contour, extract_contours, extract_material_contours};
+use crate::marching_squares::SaddleResolution;
+use crate::material::Material;
+use crate::voxel_map::VoxelMap;
+
+// --- SVG 导出：把轮廓写成闭合路径，按奇偶规则填充，洞会被正确挖空 ---
+
+#[derive(Clone, Debug)]
+pub struct SvgOptions {
+    pub scale: f32,         // 每个世界单位对应多少 SVG 单位
+    pub per_material: bool, // 每种材质一条路径，用材质的颜色填充
+    pub fill: Color,        // 不分材质时的填充色
+    pub stroke: Option<Color>,
+    pub saddle: SaddleResolution, // 和屏幕上用的一致，导出的轮廓才和看到的墙一样
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            per_material: false,
+            fill: Color::srgb(0.3, 0.3, 0.3),
+            stroke: Some(Color::BLACK),
+            saddle: SaddleResolution::default(),
+        }
+    }
+}
+
+impl VoxelMap {
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        let Some((min, max)) = self.bounds() else {
+            return svg_document(Vec2::ZERO, String::new());
+        };
+        // 画布覆盖所有区块再向外一格；SVG 的 y 轴朝下，要上下翻转
+        let top_left = self.grid_to_world(min.x - 1, max.y);
+        let bottom_right = self.grid_to_world(max.x, min.y - 1);
+        let to_svg = |p: Vec2| Vec2::new(p.x - top_left.x, top_left.y - p.y) * options.scale;
+        let size = to_svg(bottom_right);
+
+        let mut body = String::new();
+        if options.per_material {
+            for material in Material::ALL {
+                let contours = extract_material_contours(self, ISO_LEVEL, material, options.saddle);
+                push_path(&mut body, &contours, material.color(), options, to_svg);
+            }
+        } else {
+            let contours = extract_contours(self, ISO_LEVEL, options.saddle);
+            push_path(&mut body, &contours, options.fill, options, to_svg);
+        }
+        svg_document(size, body)
+    }
+
+    pub fn export_svg(&self, path: impl AsRef<Path>, options: &SvgOptions) -> io::Result<()> {
+        std::fs::write(path, self.to_svg(options))
+    }
+}
+
+fn svg_document(size: Vec2, body: String) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n{body}</svg>\n",
+        w = size.x,
+        h = size.y,
+    )
+}
+
+// 所有轮廓放进同一条 path，岛屿和洞靠 evenodd 区分
+fn push_path(
+    out: &mut String,
+    contours: &[Contour],
+    fill: Color,
+    options: &SvgOptions,
+    to_svg: impl Fn(Vec2) -> Vec2,
+) {
+    let mut data = String::new();
+    for contour in contours.iter().filter(|c| c.points.len() >= 2) {
+        for (i, point) in contour.points.iter().enumerate() {
+            let p = to_svg(*point);
+            let command = if i == 0 { 'M' } else { 'L' };
+            let _ = write!(data, "{command}{} {} ", p.x, p.y);
+        }
+        if contour.closed {
+            data.push_str("Z ");
+        }
+    }
+    if data.is_empty() {
+        return;
+    }
+
+    let stroke = match options.stroke {
+        Some(color) => format!(
+            " stroke=\"{}\" stroke-width=\"{}\"",
+            color.to_srgba().to_hex(),
+            options.scale
+        ),
+        None => String::new(),
+    };
+    let _ = writeln!(
+        out,
+        "  <path fill=\"{}\" fill-rule=\"evenodd\"{stroke} d=\"{}\"/>",
+        fill.to_srgba().to_hex(),
+        data.trim_end()
+    );
+}
+
+// --- 插件：F7 导出 SVG，Shift+F7 按材质分色导出 ---
+pub struct SvgExportPlugin;
+
+impl Plugin for SvgExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SvgPath>().add_systems(Update, svg_keys);
+    }
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct SvgPath(pub String);
+
+impl Default for SvgPath {
+    fn default() -> Self {
+        Self("terrain.svg".to_string())
+    }
+}
+
+fn svg_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    path: Res<SvgPath>,
+    saddle: Option<Res<SaddleResolution>>,
+    map: Res<VoxelMap>,
+) {
+    if !keys.just_pressed(KeyCode::F7) {
+        return;
+    }
+    let options = SvgOptions {
+        per_material: keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]),
+        saddle: saddle.map(|s| *s).unwrap_or_default(),
+        ..default()
+    };
+    match map.export_svg(&path.0, &options) {
+        Ok(()) => info!("exported contours to {}", path.0),
+        Err(err) => error!("failed to export {}: {err}", path.0),
+    }
+}
@@ -0,0 +1,393 @@
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+
+use crate::history::{CellChange, EditDelta};
+use crate::material::Material;
+use crate::{CHUNK_SIZE, ISO_LEVEL, VOXEL_SIZE};
+
+// --- 区块：固定大小的一块密度数据 ---
+pub struct Chunk {
+    data: Vec<f32>,           // CHUNK_SIZE * CHUNK_SIZE 的扁平数组
+    materials: Vec<Material>, // 每个格子的材质，和 data 一一对应
+    solid_count: usize,       // 密度 > 0 的格子数，为 0 时区块可以被回收
+}
+
+impl Chunk {
+    fn new() -> Self {
+        let len = (CHUNK_SIZE * CHUNK_SIZE) as usize;
+        Self {
+            data: vec![0.0; len],
+            materials: vec![Material::default(); len],
+            solid_count: 0,
+        }
+    }
+
+    fn index(local: IVec2) -> usize {
+        (local.y * CHUNK_SIZE + local.x) as usize
+    }
+
+    pub fn get(&self, local: IVec2) -> f32 {
+        self.data[Self::index(local)]
+    }
+
+    fn set(&mut self, local: IVec2, value: f32) {
+        let idx = Self::index(local);
+        let old = self.data[idx];
+        if old > 0.0 {
+            self.solid_count -= 1;
+        }
+        if value > 0.0 {
+            self.solid_count += 1;
+        }
+        self.data[idx] = value;
+    }
+
+    pub fn material(&self, local: IVec2) -> Material {
+        self.materials[Self::index(local)]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.solid_count == 0
+    }
+}
+
+// --- 资源定义：按区块存储的无限地图 ---
+// 没有区块的地方一律视为空气（密度 0.0）
+#[derive(Resource)]
+pub struct VoxelMap {
+    chunks: HashMap<IVec2, Chunk>,
+    origin: Vec2, // 网格 (0, 0) 在世界坐标中的位置
+    // 需要重新计算轮廓的格子范围，按格子左下角所在的区块分组，(min, max) 都包含在内
+    dirty: HashMap<IVec2, (IVec2, IVec2)>,
+    // 正在记录的一次编辑：每个被改过的格点第一次被改之前的值
+    recording: Option<HashMap<IVec2, (f32, Material)>>,
+    recording_mined: [f32; Material::ALL.len()], // 开始记录时的 mined，结束时算出这一步挖了多少
+    mined: [f32; Material::ALL.len()],           // 每种材质被挖掉的密度总量，按材质 id 索引
+}
+
+impl VoxelMap {
+    // 创建一张 width x height 的实心地图，并让它在世界原点居中
+    pub fn new(width: usize, height: usize) -> Self {
+        let origin = -Vec2::new(width as f32, height as f32) * VOXEL_SIZE / 2.0;
+        let mut map = Self::empty(origin);
+        for y in 0..height as i32 {
+            for x in 0..width as i32 {
+                map.set_density(x, y, 1.0); // 初始化全是 1.0 (实心)
+            }
+        }
+        map
+    }
+
+    // 创建一张全是空气的地图
+    pub fn empty(origin: Vec2) -> Self {
+        Self {
+            chunks: HashMap::default(),
+            origin,
+            dirty: HashMap::default(),
+            recording: None,
+            recording_mined: [0.0; Material::ALL.len()],
+            mined: [0.0; Material::ALL.len()],
+        }
+    }
+
+    // 网格坐标 -> (区块坐标, 区块内坐标)，负数坐标也能正确落到对应区块
+    pub fn split_coord(x: i32, y: i32) -> (IVec2, IVec2) {
+        let chunk = IVec2::new(x.div_euclid(CHUNK_SIZE), y.div_euclid(CHUNK_SIZE));
+        let local = IVec2::new(x.rem_euclid(CHUNK_SIZE), y.rem_euclid(CHUNK_SIZE));
+        (chunk, local)
+    }
+
+    pub fn origin(&self) -> Vec2 {
+        self.origin
+    }
+
+    // 辅助：获取世界坐标对应的网格坐标
+    pub fn world_to_grid(&self, world_pos: Vec2) -> (i32, i32) {
+        let grid = ((world_pos - self.origin) / VOXEL_SIZE).floor();
+        (grid.x as i32, grid.y as i32)
+    }
+
+    // 辅助：网格坐标对应的世界坐标（格点本身的位置）
+    pub fn grid_to_world(&self, x: i32, y: i32) -> Vec2 {
+        self.origin + Vec2::new(x as f32, y as f32) * VOXEL_SIZE
+    }
+
+    // 安全获取密度（没有区块的地方返回 0.0）
+    pub fn get_density(&self, x: i32, y: i32) -> f32 {
+        let (chunk, local) = Self::split_coord(x, y);
+        self.chunks.get(&chunk).map_or(0.0, |c| c.get(local))
+    }
+
+    // 直接写入密度，必要时创建区块，区块变空时把它删掉
+    pub fn set_density(&mut self, x: i32, y: i32, value: f32) {
+        let value = value.clamp(0.0, 1.0);
+        if value == self.get_density(x, y) {
+            return;
+        }
+        self.record(x, y);
+        self.mark_dirty(x, y);
+
+        let (key, local) = Self::split_coord(x, y);
+        match self.chunks.get_mut(&key) {
+            Some(chunk) => {
+                chunk.set(local, value);
+                if chunk.is_empty() {
+                    self.chunks.remove(&key);
+                }
+            }
+            None if value > 0.0 => {
+                let mut chunk = Chunk::new();
+                chunk.set(local, value);
+                self.chunks.insert(key, chunk);
+            }
+            None => {}
+        }
+    }
+
+    // 修改密度
+    pub fn modify_density(&mut self, x: i32, y: i32, amount: f32) {
+        let value = self.get_density(x, y) + amount;
+        self.set_density(x, y, value);
+    }
+
+    // 没有区块的地方返回默认材质
+    pub fn get_material(&self, x: i32, y: i32) -> Material {
+        let (chunk, local) = Self::split_coord(x, y);
+        self.chunks
+            .get(&chunk)
+            .map_or(Material::default(), |c| c.material(local))
+    }
+
+    // 修改材质；空气里没有区块的地方不需要记录材质
+    pub fn set_material(&mut self, x: i32, y: i32, material: Material) {
+        let (key, local) = Self::split_coord(x, y);
+        let idx = Chunk::index(local);
+        if self
+            .chunks
+            .get(&key)
+            .is_none_or(|chunk| chunk.materials[idx] == material)
+        {
+            return;
+        }
+        self.record(x, y);
+        if let Some(chunk) = self.chunks.get_mut(&key) {
+            chunk.materials[idx] = material;
+        }
+        self.mark_dirty(x, y);
+    }
+
+    // 同时写入密度和材质
+    pub fn set_voxel(&mut self, x: i32, y: i32, density: f32, material: Material) {
+        self.set_density(x, y, density);
+        self.set_material(x, y, material);
+    }
+
+    // 挖掘：挖掉的量按材质硬度缩小，基岩挖不动
+    pub fn dig_density(&mut self, x: i32, y: i32, amount: f32) {
+        self.dig_density_to(x, y, amount, 0.0);
+    }
+
+    // 挖掘，但密度最多降到 floor（已经低于 floor 的格子不变），挖掉的量计入 mined
+    pub fn dig_density_to(&mut self, x: i32, y: i32, amount: f32, floor: f32) {
+        let material = self.get_material(x, y);
+        let removed = self.erode_density_to(x, y, amount, floor);
+        self.mined[material.id() as usize] += removed;
+    }
+
+    // 和 dig_density_to 一样受硬度影响，但不算挖矿（平滑、压平之类的笔刷用），返回降低了多少
+    pub fn erode_density_to(&mut self, x: i32, y: i32, amount: f32, floor: f32) -> f32 {
+        let hardness = self.get_material(x, y).hardness();
+        let density = self.get_density(x, y);
+        if !hardness.is_finite() || density <= floor {
+            return 0.0;
+        }
+        let value = (density - amount.abs() / hardness).max(floor);
+        self.set_density(x, y, value);
+        density - value
+    }
+
+    // 填充：往空气里填的时候格子变成指定的材质，已经是墙的格子保留原来的材质
+    pub fn fill_density(&mut self, x: i32, y: i32, amount: f32, material: Material) {
+        self.fill_density_to(x, y, amount, 1.0, material);
+    }
+
+    // 填充，但密度最多升到 ceil（已经高于 ceil 的格子不变）
+    pub fn fill_density_to(&mut self, x: i32, y: i32, amount: f32, ceil: f32, material: Material) {
+        let density = self.get_density(x, y);
+        if density >= ceil {
+            return;
+        }
+        let value = (density + amount.abs()).min(ceil);
+        if density < ISO_LEVEL {
+            self.set_voxel(x, y, value, material);
+        } else {
+            self.set_density(x, y, value);
+        }
+    }
+
+    // 地图上某种材质的总量（密度之和）
+    pub fn material_total(&self, material: Material) -> f32 {
+        self.material_totals()[material.id() as usize]
+    }
+
+    // 所有材质的总量，按材质 id 索引
+    pub fn material_totals(&self) -> [f32; Material::ALL.len()] {
+        let mut totals = [0.0; Material::ALL.len()];
+        for chunk in self.chunks.values() {
+            for (density, material) in chunk.data.iter().zip(&chunk.materials) {
+                totals[material.id() as usize] += density;
+            }
+        }
+        totals
+    }
+
+    // 挖掘累计挖掉的量（只算 dig_density 系列，不算直接写入和 erode_density_to）
+    // 撤销/重做时跟着增减，读档、导入、重新生成后从 0 开始
+    pub fn mined(&self, material: Material) -> f32 {
+        self.mined[material.id() as usize]
+    }
+
+    // 撤销/重做一步时把这一步挖掉的量加回来或者减掉
+    pub(crate) fn add_mined(&mut self, amounts: &[f32; Material::ALL.len()], sign: f32) {
+        for (total, amount) in self.mined.iter_mut().zip(amounts) {
+            *total += amount * sign;
+        }
+    }
+
+    pub fn chunks(&self) -> impl Iterator<Item = (IVec2, &Chunk)> {
+        self.chunks.iter().map(|(k, c)| (*k, c))
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    // 所有区块覆盖的网格范围 (min, max)，max 不包含在内
+    pub fn bounds(&self) -> Option<(IVec2, IVec2)> {
+        let mut keys = self.chunks.keys();
+        let first = *keys.next()?;
+        let (min, max) = keys.fold((first, first), |(min, max), k| (min.min(*k), max.max(*k)));
+        Some((min * CHUNK_SIZE, (max + IVec2::ONE) * CHUNK_SIZE))
+    }
+
+    // 所有密度 > 0 的格子的范围 (min, max)，max 不包含在内；比 bounds 紧，不按区块对齐
+    pub fn content_bounds(&self) -> Option<(IVec2, IVec2)> {
+        let mut result: Option<(IVec2, IVec2)> = None;
+        for (key, chunk) in &self.chunks {
+            let base = *key * CHUNK_SIZE;
+            for (i, density) in chunk.data.iter().enumerate() {
+                if *density <= 0.0 {
+                    continue;
+                }
+                let cell = base + IVec2::new(i as i32 % CHUNK_SIZE, i as i32 / CHUNK_SIZE);
+                result = Some(match result {
+                    Some((min, max)) => (min.min(cell), max.max(cell + IVec2::ONE)),
+                    None => (cell, cell + IVec2::ONE),
+                });
+            }
+        }
+        result
+    }
+
+    // 开始记录一次编辑（例如一笔笔刷），已经在记录时什么也不做
+    pub fn begin_edit(&mut self) {
+        if self.recording.is_none() {
+            self.recording = Some(HashMap::default());
+            self.recording_mined = self.mined;
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    // 结束记录，返回这期间所有格点的变化；什么都没变时返回 None
+    pub fn end_edit(&mut self) -> Option<EditDelta> {
+        let mut changes: Vec<CellChange> = self
+            .recording
+            .take()?
+            .into_iter()
+            .filter_map(|(cell, before)| {
+                let after = (
+                    self.get_density(cell.x, cell.y),
+                    self.get_material(cell.x, cell.y),
+                );
+                (before != after).then_some(CellChange {
+                    cell,
+                    before,
+                    after,
+                })
+            })
+            .collect();
+        if changes.is_empty() {
+            return None;
+        }
+        changes.sort_by_key(|change| (change.cell.y, change.cell.x));
+        let mut mined = self.mined;
+        for (amount, before) in mined.iter_mut().zip(&self.recording_mined) {
+            *amount -= before;
+        }
+        Some(EditDelta { changes, mined })
+    }
+
+    // 格点第一次被修改前记下原来的值
+    fn record(&mut self, x: i32, y: i32) {
+        let before = (self.get_density(x, y), self.get_material(x, y));
+        if let Some(recording) = &mut self.recording {
+            recording.entry(IVec2::new(x, y)).or_insert(before);
+        }
+    }
+
+    // 记录一个格点被修改：用到它的格子再加一圈边框都需要重算
+    fn mark_dirty(&mut self, x: i32, y: i32) {
+        self.mark_dirty_rect(IVec2::new(x - 2, y - 2), IVec2::new(x + 1, y + 1));
+    }
+
+    // 把一块格子范围（包含 min 和 max）按区块拆开后合并进脏区域
+    pub fn mark_dirty_rect(&mut self, min: IVec2, max: IVec2) {
+        let (min_chunk, _) = Self::split_coord(min.x, min.y);
+        let (max_chunk, _) = Self::split_coord(max.x, max.y);
+        for cy in min_chunk.y..=max_chunk.y {
+            for cx in min_chunk.x..=max_chunk.x {
+                let chunk = IVec2::new(cx, cy);
+                let base = chunk * CHUNK_SIZE;
+                let lo = min.max(base);
+                let hi = max.min(base + IVec2::splat(CHUNK_SIZE - 1));
+                self.dirty
+                    .entry(chunk)
+                    .and_modify(|(min, max)| {
+                        *min = min.min(lo);
+                        *max = max.max(hi);
+                    })
+                    .or_insert((lo, hi));
+            }
+        }
+    }
+
+    // 整张地图都需要重算（例如切换了 Marching Squares 的设置）
+    pub fn mark_all_dirty(&mut self) {
+        let keys: Vec<IVec2> = self.chunks.keys().copied().collect();
+        for key in keys {
+            let base = key * CHUNK_SIZE;
+            self.mark_dirty_rect(base - IVec2::ONE, base + IVec2::splat(CHUNK_SIZE - 1));
+        }
+    }
+
+    // 整张换成另一张地图（读档、导入），旧地图的区块也要重算，否则会留着原来的轮廓
+    pub fn replace(&mut self, mut other: VoxelMap) {
+        self.mark_all_dirty();
+        for (_, (min, max)) in self.dirty.drain() {
+            other.mark_dirty_rect(min, max);
+        }
+        *self = other;
+    }
+
+    pub fn has_dirty(&self) -> bool {
+        !self.dirty.is_empty()
+    }
+
+    // 取出并清空所有脏区域
+    pub fn take_dirty(&mut self) -> Vec<(IVec2, (IVec2, IVec2))> {
+        self.dirty.drain().collect()
+    }
+}
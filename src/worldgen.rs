@@ -0,0 +1,685 @@
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::history::EditHistory;
+use crate::material::Material;
+use crate::noise::{Fbm, Perlin};
+use crate::voxel_map::VoxelMap;
+use crate::{CHUNK_SIZE, GRID_HEIGHT, GRID_WIDTH, ISO_LEVEL, VOXEL_SIZE};
+
+// --- 世界生成：启动时按配置里的种子生成地图，G 换一个种子重新生成，Shift+G 切换生成器 ---
+pub struct WorldGenPlugin;
+
+impl Plugin for WorldGenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldGenConfig>()
+            .init_resource::<WorldGenPipeline>()
+            .add_systems(Update, (regenerate_world, report_resources));
+    }
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct WorldGenConfig {
+    pub seed: u64,
+    pub width: usize, // 格子数
+    pub height: usize,
+    pub generator: Generator, // 当前使用的预设流水线
+}
+
+impl Default for WorldGenConfig {
+    fn default() -> Self {
+        Self {
+            seed: 1,
+            width: GRID_WIDTH,
+            height: GRID_HEIGHT,
+            generator: Generator::default(),
+        }
+    }
+}
+
+// 预设的流水线
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum Generator {
+    #[default]
+    Noise,
+    Cellular,
+    Surface, // 横版地表
+}
+
+impl Generator {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Noise => Self::Cellular,
+            Self::Cellular => Self::Surface,
+            Self::Surface => Self::Noise,
+        }
+    }
+}
+
+// --- 生成步骤：每次只处理一个区块里的格子 ---
+pub trait WorldGenStep: Send + Sync {
+    fn apply(&self, region: &mut GenRegion);
+}
+
+// 一个步骤在一个区块上运行时看到的东西
+// 读可以读整张地图，写只会落在 [min, max) 里，超出的写入直接忽略
+pub struct GenRegion<'a> {
+    map: &'a mut VoxelMap,
+    step: usize,
+    pub seed: u64, // 世界种子，跨区块连续的东西（噪声）用它
+    pub chunk: IVec2,
+    pub min: IVec2, // 这次要生成的格子，max 不包含
+    pub max: IVec2,
+    pub world_min: IVec2, // 整个世界的范围，max 不包含
+    pub world_max: IVec2,
+    pub rng: ChaCha8Rng, // 由种子、步骤序号和区块坐标决定，和区块的处理顺序无关
+}
+
+impl GenRegion<'_> {
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        (self.min.x..self.max.x).contains(&x) && (self.min.y..self.max.y).contains(&y)
+    }
+
+    // 区块内的所有格子，按行优先
+    pub fn cells(&self) -> impl Iterator<Item = IVec2> + use<> {
+        let (min, max) = (self.min, self.max);
+        (min.y..max.y).flat_map(move |y| (min.x..max.x).map(move |x| IVec2::new(x, y)))
+    }
+
+    pub fn density(&self, x: i32, y: i32) -> f32 {
+        self.map.get_density(x, y)
+    }
+
+    pub fn material(&self, x: i32, y: i32) -> Material {
+        self.map.get_material(x, y)
+    }
+
+    pub fn set_density(&mut self, x: i32, y: i32, value: f32) {
+        if self.contains(x, y) {
+            self.map.set_density(x, y, value);
+        }
+    }
+
+    pub fn set_material(&mut self, x: i32, y: i32, material: Material) {
+        if self.contains(x, y) {
+            self.map.set_material(x, y, material);
+        }
+    }
+
+    pub fn set_voxel(&mut self, x: i32, y: i32, density: f32, material: Material) {
+        if self.contains(x, y) {
+            self.map.set_voxel(x, y, density, material);
+        }
+    }
+
+    // 同一步骤里另一个区块的随机数；跨区块的结构（比如矿脉）用它得到和邻居一致的结果
+    pub fn rng_for(&self, chunk: IVec2) -> ChaCha8Rng {
+        ChaCha8Rng::seed_from_u64(chunk_seed(self.seed, self.step, chunk))
+    }
+
+    // 混入了步骤序号的世界种子：同一种步骤在流水线里放两次，得到的结果不一样
+    pub fn step_seed(&self) -> u64 {
+        step_seed(self.seed, self.step)
+    }
+}
+
+pub fn step_seed(seed: u64, step: usize) -> u64 {
+    splitmix64(seed ^ step as u64)
+}
+
+// 把种子、步骤序号和区块坐标混成一个种子（splitmix64）
+fn chunk_seed(seed: u64, step: usize, chunk: IVec2) -> u64 {
+    let mut h = seed;
+    for v in [step as u64, chunk.x as u32 as u64, chunk.y as u32 as u64] {
+        h = splitmix64(h ^ v);
+    }
+    h
+}
+
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+// 按格子坐标得到的 0..1 随机数，不依赖任何遍历顺序
+pub fn cell_random(seed: u64, x: i32, y: i32) -> f32 {
+    let h = splitmix64(splitmix64(seed ^ x as u32 as u64) ^ y as u32 as u64);
+    (h >> 40) as f32 / (1u64 << 24) as f32
+}
+
+// --- 流水线：按顺序执行所有步骤，每个步骤按区块坐标顺序跑完整个世界再进入下一步 ---
+#[derive(Resource)]
+pub struct WorldGenPipeline {
+    steps: Vec<Box<dyn WorldGenStep>>,
+}
+
+impl Default for WorldGenPipeline {
+    fn default() -> Self {
+        Self::preset(Generator::default())
+    }
+}
+
+impl WorldGenPipeline {
+    // 空的流水线
+    pub fn new() -> Self {
+        Self { steps: Vec::new() }
+    }
+
+    pub fn with_step(mut self, step: impl WorldGenStep + 'static) -> Self {
+        self.push(step);
+        self
+    }
+
+    pub fn push(&mut self, step: impl WorldGenStep + 'static) {
+        self.steps.push(Box::new(step));
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    pub fn preset(generator: Generator) -> Self {
+        let mut pipeline = match generator {
+            Generator::Noise => Self::new().with_step(NoiseCaves::default()),
+            Generator::Cellular => Self::new().with_step(CellularCaves::default()),
+            Generator::Surface => Self::new().with_step(SurfaceTerrain::default()),
+        };
+        for ore in OreVeins::defaults() {
+            pipeline.push(ore);
+        }
+        // 地表上方是天空，不要封顶
+        pipeline.with_step(BedrockBorder {
+            top: generator != Generator::Surface,
+            ..default()
+        })
+    }
+
+    // 生成一张 width x height 的地图，和 VoxelMap::new 一样在世界原点居中
+    pub fn generate(&self, config: &WorldGenConfig) -> VoxelMap {
+        let (width, height) = (config.width as i32, config.height as i32);
+        let origin = -Vec2::new(width as f32, height as f32) * VOXEL_SIZE / 2.0;
+        let mut map = VoxelMap::empty(origin);
+        self.run(
+            &mut map,
+            config.seed,
+            IVec2::ZERO,
+            IVec2::new(width, height),
+        );
+        map
+    }
+
+    // 在已有地图的 [min, max) 范围上执行所有步骤
+    pub fn run(&self, map: &mut VoxelMap, seed: u64, min: IVec2, max: IVec2) {
+        if min.cmpge(max).any() {
+            return;
+        }
+        let (min_chunk, _) = VoxelMap::split_coord(min.x, min.y);
+        let (max_chunk, _) = VoxelMap::split_coord(max.x - 1, max.y - 1);
+
+        for (step, generator) in self.steps.iter().enumerate() {
+            for cy in min_chunk.y..=max_chunk.y {
+                for cx in min_chunk.x..=max_chunk.x {
+                    let chunk = IVec2::new(cx, cy);
+                    let base = chunk * CHUNK_SIZE;
+                    let mut region = GenRegion {
+                        map: &mut *map,
+                        step,
+                        seed,
+                        chunk,
+                        min: base.max(min),
+                        max: (base + IVec2::splat(CHUNK_SIZE)).min(max),
+                        world_min: min,
+                        world_max: max,
+                        rng: ChaCha8Rng::seed_from_u64(chunk_seed(seed, step, chunk)),
+                    };
+                    generator.apply(&mut region);
+                }
+            }
+        }
+    }
+}
+
+// 分形噪声洞穴：噪声值加上偏移后映射成密度
+#[derive(Clone, Copy, Debug)]
+pub struct NoiseCaves {
+    pub fbm: Fbm,
+    pub bias: f32,     // 越大墙越多，0 大约一半是洞
+    pub contrast: f32, // 噪声到密度的放大倍数，越大洞壁越陡
+}
+
+impl Default for NoiseCaves {
+    fn default() -> Self {
+        Self {
+            fbm: Fbm::default(),
+            bias: 0.15,
+            contrast: 2.0,
+        }
+    }
+}
+
+impl NoiseCaves {
+    pub fn density(&self, noise: &Perlin, x: i32, y: i32) -> f32 {
+        let n = self.fbm.sample(noise, Vec2::new(x as f32, y as f32));
+        (0.5 + (n + self.bias) * self.contrast).clamp(0.0, 1.0)
+    }
+}
+
+impl WorldGenStep for NoiseCaves {
+    fn apply(&self, region: &mut GenRegion) {
+        let noise = Perlin::new(region.seed);
+        for cell in region.cells() {
+            let density = self.density(&noise, cell.x, cell.y);
+            region.set_density(cell.x, cell.y, density);
+        }
+    }
+}
+
+// 元胞自动机数邻居的范围，半径都是 1
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum Neighbourhood {
+    #[default]
+    Moore, // 周围 8 格
+    VonNeumann, // 上下左右 4 格，birth / survive 要相应调小
+}
+
+// 元胞自动机洞穴：随机撒墙，反复按邻居数决定生死，最后模糊成平滑的密度
+#[derive(Clone, Copy, Debug)]
+pub struct CellularCaves {
+    pub fill: f32,       // 初始是墙的比例
+    pub iterations: u32, // 自动机迭代次数
+    pub birth: u32,      // 空地周围至少有这么多墙时变成墙
+    pub survive: u32,    // 墙周围至少有这么多墙时保留
+    pub neighbourhood: Neighbourhood,
+    pub blur_passes: u32, // 3x3 均值模糊的次数，0 表示保留方块状的 0/1
+}
+
+impl Default for CellularCaves {
+    fn default() -> Self {
+        Self {
+            fill: 0.5,
+            iterations: 5,
+            birth: 5,
+            survive: 4,
+            neighbourhood: Neighbourhood::Moore,
+            blur_passes: 1,
+        }
+    }
+}
+
+impl CellularCaves {
+    // 在 [min, max) 的窗口里模拟，返回按行优先的密度；作为流水线的一步时 seed 是 GenRegion::step_seed
+    // 窗口外一律算作墙，所以窗口边缘附近的结果不可靠，调用方要留够余量
+    pub fn simulate(&self, seed: u64, min: IVec2, max: IVec2) -> Vec<f32> {
+        let size = (max - min).max(IVec2::ZERO);
+        let (width, height) = (size.x as usize, size.y as usize);
+        let mut grid: Vec<f32> = (0..width * height)
+            .map(|i| {
+                let (x, y) = (min.x + (i % width) as i32, min.y + (i / width) as i32);
+                if cell_random(seed, x, y) < self.fill {
+                    1.0
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+
+        for _ in 0..self.iterations {
+            grid = (0..width * height)
+                .map(|i| {
+                    let walls = neighbour_walls(
+                        &grid,
+                        width,
+                        height,
+                        i % width,
+                        i / width,
+                        self.neighbourhood,
+                    );
+                    let limit = if grid[i] > 0.5 {
+                        self.survive
+                    } else {
+                        self.birth
+                    };
+                    if walls >= limit { 1.0 } else { 0.0 }
+                })
+                .collect();
+        }
+        for _ in 0..self.blur_passes {
+            grid = blur(&grid, width, height);
+        }
+        grid
+    }
+}
+
+impl WorldGenStep for CellularCaves {
+    fn apply(&self, region: &mut GenRegion) {
+        // 每一轮只影响一圈邻居，向外多算这么多格，区块接缝处和整张一起算的结果完全一样
+        let margin = IVec2::splat((self.iterations + self.blur_passes) as i32 + 1);
+        let min = (region.min - margin).max(region.world_min);
+        let max = (region.max + margin).min(region.world_max);
+        let grid = self.simulate(region.step_seed(), min, max);
+
+        let width = (max.x - min.x) as usize;
+        for cell in region.cells() {
+            let local = cell - min;
+            let density = grid[local.y as usize * width + local.x as usize];
+            region.set_density(cell.x, cell.y, density);
+        }
+    }
+}
+
+// 邻域里墙的数量，网格外面算作墙，洞穴不会通到边界外
+fn neighbour_walls(
+    grid: &[f32],
+    width: usize,
+    height: usize,
+    x: usize,
+    y: usize,
+    neighbourhood: Neighbourhood,
+) -> u32 {
+    let mut walls = 0;
+    for dy in -1..=1_i32 {
+        for dx in -1..=1_i32 {
+            let skip = match neighbourhood {
+                Neighbourhood::Moore => dx == 0 && dy == 0,
+                Neighbourhood::VonNeumann => dx.abs() + dy.abs() != 1,
+            };
+            if skip {
+                continue;
+            }
+            let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+            let inside = (0..width as i32).contains(&nx) && (0..height as i32).contains(&ny);
+            if !inside || grid[ny as usize * width + nx as usize] > 0.5 {
+                walls += 1;
+            }
+        }
+    }
+    walls
+}
+
+// 3x3 均值模糊，边缘只取网格内的格子
+pub fn blur(grid: &[f32], width: usize, height: usize) -> Vec<f32> {
+    (0..width * height)
+        .map(|i| {
+            let (x, y) = ((i % width) as i32, (i / width) as i32);
+            let mut sum = 0.0;
+            let mut count = 0.0;
+            for ny in (y - 1).max(0)..=(y + 1).min(height as i32 - 1) {
+                for nx in (x - 1).max(0)..=(x + 1).min(width as i32 - 1) {
+                    sum += grid[ny as usize * width + nx as usize];
+                    count += 1.0;
+                }
+            }
+            sum / count
+        })
+        .collect()
+}
+
+// 世界边缘一圈挖不动的基岩
+#[derive(Clone, Copy, Debug)]
+pub struct BedrockBorder {
+    pub thickness: i32,
+    pub top: bool, // 顶上也封住
+}
+
+impl Default for BedrockBorder {
+    fn default() -> Self {
+        Self {
+            thickness: 2,
+            top: true,
+        }
+    }
+}
+
+impl WorldGenStep for BedrockBorder {
+    fn apply(&self, region: &mut GenRegion) {
+        let inner_min = region.world_min + IVec2::splat(self.thickness);
+        let mut inner_max = region.world_max - IVec2::splat(self.thickness);
+        if !self.top {
+            inner_max.y = region.world_max.y;
+        }
+        for cell in region.cells() {
+            if cell.cmplt(inner_min).any() || cell.cmpge(inner_max).any() {
+                region.set_voxel(cell.x, cell.y, 1.0, Material::Bedrock);
+            }
+        }
+    }
+}
+
+// 横版地表：一维噪声给出地面高度，地面上下按到地面的有符号距离给密度，
+// 插值出来的地面线是平滑的；可选的二维噪声让地表出现悬崖和浮空的土块
+#[derive(Clone, Copy, Debug)]
+pub struct SurfaceTerrain {
+    pub height: f32,    // 平均地面高度，占世界高度的比例
+    pub amplitude: f32, // 地面起伏的幅度（格子数）
+    pub fbm: Fbm,       // 地面高度的一维噪声
+    pub overhangs: Option<Overhangs>,
+    pub grass_depth: f32, // 地面往下多少格是草
+    pub dirt_depth: f32,  // 草下面多少格是泥土，再往下是石头
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Overhangs {
+    pub fbm: Fbm,
+    pub strength: f32, // 叠加到距离上的幅度（格子数）
+}
+
+impl Default for SurfaceTerrain {
+    fn default() -> Self {
+        Self {
+            height: 0.6,
+            amplitude: 12.0,
+            fbm: Fbm {
+                octaves: 4,
+                frequency: 0.02,
+                ..default()
+            },
+            overhangs: Some(Overhangs {
+                fbm: Fbm {
+                    octaves: 3,
+                    frequency: 0.08,
+                    ..default()
+                },
+                strength: 4.0,
+            }),
+            grass_depth: 1.5,
+            dirt_depth: 6.0,
+        }
+    }
+}
+
+impl SurfaceTerrain {
+    // 第 x 列的地面高度（网格坐标，可以是小数）
+    pub fn surface(&self, noise: &Perlin, world_min: IVec2, world_max: IVec2, x: f32) -> f32 {
+        let base = world_min.y as f32 + (world_max.y - world_min.y) as f32 * self.height;
+        base + self.fbm.sample_1d(noise, x) * self.amplitude
+    }
+}
+
+impl WorldGenStep for SurfaceTerrain {
+    fn apply(&self, region: &mut GenRegion) {
+        let noise = Perlin::new(region.seed);
+        let overhang_noise = Perlin::new(splitmix64(region.seed)); // 和地面高度用不同的噪声
+        let (world_min, world_max) = (region.world_min, region.world_max);
+        let surface = |x: f32| self.surface(&noise, world_min, world_max, x);
+
+        for cell in region.cells() {
+            let x = cell.x as f32;
+            let ground = surface(x);
+            // 竖直距离除以坡度得到到地面线的近似距离，陡坡上的过渡带也不会被拉宽
+            let slope = (surface(x + 0.5) - surface(x - 0.5)).abs();
+            let depth = (ground - cell.y as f32) / (1.0 + slope * slope).sqrt();
+
+            let mut distance = depth;
+            if let Some(overhangs) = &self.overhangs {
+                let p = Vec2::new(x, cell.y as f32);
+                distance += overhangs.fbm.sample(&overhang_noise, p) * overhangs.strength;
+            }
+            // 两个格子宽的线性过渡：地面线正好落在 0.5 上
+            let density = (0.5 + distance * 0.5).clamp(0.0, 1.0);
+
+            let material = if depth < self.grass_depth {
+                Material::Grass
+            } else if depth < self.grass_depth + self.dirt_depth {
+                Material::Dirt
+            } else {
+                Material::Stone
+            };
+            region.set_voxel(cell.x, cell.y, density, material);
+        }
+    }
+}
+
+// 矿脉：每个区块按稀有度撒几条随机游走，走过的地方把墙换成矿石（密度不变）
+#[derive(Clone, Debug)]
+pub struct OreVeins {
+    pub material: Material,
+    pub rarity: f32,       // 每个区块平均几条矿脉
+    pub length: u32,       // 随机游走的步数
+    pub radius: f32,       // 矿脉的粗细（格子数）
+    pub depth: (f32, f32), // 起点离世界顶部的深度范围，占世界高度的比例
+}
+
+impl OreVeins {
+    // 煤浅而多，铁居中，金深而少
+    pub fn defaults() -> [OreVeins; 3] {
+        [
+            OreVeins {
+                material: Material::Coal,
+                rarity: 0.8,
+                length: 10,
+                radius: 1.2,
+                depth: (0.1, 0.7),
+            },
+            OreVeins {
+                material: Material::Iron,
+                rarity: 0.5,
+                length: 8,
+                radius: 1.0,
+                depth: (0.3, 0.9),
+            },
+            OreVeins {
+                material: Material::Gold,
+                rarity: 0.3,
+                length: 6,
+                radius: 0.8,
+                depth: (0.6, 1.0),
+            },
+        ]
+    }
+
+    // 起点在 chunk 里的所有矿脉，每条返回游走经过的点
+    fn veins(&self, region: &GenRegion, chunk: IVec2) -> Vec<Vec<Vec2>> {
+        let mut rng = region.rng_for(chunk);
+        let mut count = self.rarity.floor() as u32;
+        if rng.random::<f32>() < self.rarity.fract() {
+            count += 1;
+        }
+
+        let top = region.world_max.y as f32;
+        let height = (region.world_max.y - region.world_min.y) as f32;
+        let base = (chunk * CHUNK_SIZE).as_vec2();
+        let mut veins = Vec::new();
+        for _ in 0..count {
+            let mut p = base + Vec2::new(rng.random(), rng.random()) * CHUNK_SIZE as f32;
+            // 随机数照样取完，保证后面的矿脉不受这条是否被跳过的影响
+            let steps: Vec<Vec2> = (0..self.length)
+                .map(|_| Vec2::new(rng.random_range(-1.0..=1.0), rng.random_range(-1.0..=1.0)))
+                .collect();
+            let depth = (top - p.y) / height;
+            if depth < self.depth.0 || depth > self.depth.1 {
+                continue;
+            }
+            let mut points = vec![p];
+            for step in steps {
+                p += step;
+                points.push(p);
+            }
+            veins.push(points);
+        }
+        veins
+    }
+}
+
+impl WorldGenStep for OreVeins {
+    fn apply(&self, region: &mut GenRegion) {
+        // 矿脉可能从相邻区块延伸过来，游走长度不超过一个区块时看一圈邻居就够了
+        let reach = (self.length as f32 + self.radius).ceil() as i32 / CHUNK_SIZE + 1;
+        let mut points = Vec::new();
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                points.extend(
+                    self.veins(region, region.chunk + IVec2::new(dx, dy))
+                        .concat(),
+                );
+            }
+        }
+
+        let r = self.radius.ceil() as i32;
+        for p in points {
+            let center = p.round().as_ivec2();
+            for y in center.y - r..=center.y + r {
+                for x in center.x - r..=center.x + r {
+                    let inside = Vec2::new(x as f32, y as f32).distance(p) <= self.radius;
+                    // 只替换实心的普通岩层，空气和基岩不动
+                    if inside
+                        && region.contains(x, y)
+                        && region.density(x, y) >= ISO_LEVEL
+                        && region.material(x, y) != Material::Bedrock
+                    {
+                        region.set_material(x, y, self.material);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// --- 系统：I 打印每种资源的储量和已经挖掉的量 ---
+fn report_resources(keys: Res<ButtonInput<KeyCode>>, map: Res<VoxelMap>) {
+    if !keys.just_pressed(KeyCode::KeyI) {
+        return;
+    }
+    let totals = map.material_totals();
+    for material in Material::ALL {
+        info!(
+            "{}: {:.1} left, {:.1} mined",
+            material.name(),
+            totals[material.id() as usize],
+            map.mined(material)
+        );
+    }
+}
+
+// --- 系统：G 用下一个种子重新生成，Shift+G 换一种生成器 ---
+fn regenerate_world(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut config: ResMut<WorldGenConfig>,
+    mut pipeline: ResMut<WorldGenPipeline>,
+    mut map: ResMut<VoxelMap>,
+    mut history: Option<ResMut<EditHistory>>,
+) {
+    if !keys.just_pressed(KeyCode::KeyG) {
+        return;
+    }
+    if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        config.generator = config.generator.next();
+        *pipeline = WorldGenPipeline::preset(config.generator);
+    } else {
+        config.seed = config.seed.wrapping_add(1);
+    }
+    map.replace(pipeline.generate(&config));
+    if let Some(history) = history.as_mut() {
+        history.clear();
+    }
+    info!(
+        "generated {:?} world with seed {}",
+        config.generator, config.seed
+    );
+}
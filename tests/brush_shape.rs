@@ -0,0 +1,125 @@
+use bevy::prelude::*;
+use voxel_2d::brush::BrushSettings;
+use voxel_2d::brush_shape::Footprint;
+use voxel_2d::contour::extract_contours;
+use voxel_2d::marching_squares::SaddleResolution;
+use voxel_2d::voxel_map::VoxelMap;
+use voxel_2d::{ISO_LEVEL, VOXEL_SIZE};
+
+// 抗锯齿之后，覆盖率 0.5 的等值线应该正好落在形状的边界上
+// 直边上插值是精确的，圆弧附近距离场不是严格线性的，留一点余量
+const TOLERANCE: f32 = VOXEL_SIZE * 0.1;
+// 尖角切不出来（一个格子里只有一条线段），尖角附近的点不检查
+const CORNER_RADIUS: f32 = VOXEL_SIZE * 1.5;
+
+fn corners(footprint: &Footprint) -> Vec<Vec2> {
+    match footprint {
+        Footprint::Box {
+            center,
+            half_size,
+            angle,
+        } => [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+            .into_iter()
+            .map(|(x, y)| *center + Rot2::radians(*angle) * (*half_size * Vec2::new(x, y)))
+            .collect(),
+        Footprint::Capsule { .. } => Vec::new(),
+        Footprint::Polygon(points) => points.clone(),
+    }
+}
+
+fn assert_on_boundary(points: &[Vec2], footprint: &Footprint) {
+    let corners = corners(footprint);
+    let mut checked = 0;
+    for p in points {
+        if corners.iter().any(|c| c.distance(*p) < CORNER_RADIUS) {
+            continue;
+        }
+        let d = footprint.signed_distance(*p);
+        assert!(
+            d.abs() <= TOLERANCE,
+            "{footprint:?}: {p} is {d} from the edge"
+        );
+        checked += 1;
+    }
+    // 别让尖角把所有点都跳过了
+    assert!(checked * 2 > points.len(), "{footprint:?}");
+}
+
+fn filled(footprint: &Footprint) -> VoxelMap {
+    let mut map = VoxelMap::empty(Vec2::splat(-200.0));
+    BrushSettings::default().carve(&mut map, footprint, 1.0, false);
+    map
+}
+
+fn shapes() -> Vec<Footprint> {
+    vec![
+        Footprint::Box {
+            center: Vec2::new(3.0, -5.0),
+            half_size: Vec2::new(40.0, 20.0),
+            angle: 0.0,
+        },
+        Footprint::Box {
+            center: Vec2::new(3.0, -5.0),
+            half_size: Vec2::new(40.0, 20.0),
+            angle: 30f32.to_radians(),
+        },
+        Footprint::Capsule {
+            a: Vec2::new(-30.0, -10.0),
+            b: Vec2::new(35.0, 25.0),
+            radius: 14.0,
+        },
+        // 凹的 L 形
+        Footprint::Polygon(vec![
+            Vec2::new(-50.0, -40.0),
+            Vec2::new(45.0, -40.0),
+            Vec2::new(45.0, -5.0),
+            Vec2::new(-10.0, -5.0),
+            Vec2::new(-10.0, 50.0),
+            Vec2::new(-50.0, 50.0),
+        ]),
+    ]
+}
+
+#[test]
+fn filled_shapes_land_on_their_boundary() {
+    for footprint in shapes() {
+        let map = filled(&footprint);
+        // 一个形状一个环
+        let contours = extract_contours(&map, ISO_LEVEL, SaddleResolution::default());
+        assert_eq!(contours.len(), 1, "{footprint:?}");
+        assert!(contours[0].is_island());
+        assert_on_boundary(&contours[0].points, &footprint);
+    }
+}
+
+#[test]
+fn dug_shapes_land_on_their_boundary() {
+    for footprint in shapes() {
+        let mut map = VoxelMap::new(60, 60);
+        // 挖的量给足，一次到底
+        BrushSettings::default().carve(&mut map, &footprint, 100.0, true);
+        let contours = extract_contours(&map, ISO_LEVEL, SaddleResolution::default());
+        let hole = contours.iter().find(|c| c.is_hole()).unwrap();
+        assert_on_boundary(&hole.points, &footprint);
+    }
+}
+
+#[test]
+fn coverage_is_half_on_the_boundary() {
+    let footprint = &shapes()[1];
+    let Footprint::Box {
+        center,
+        half_size,
+        angle,
+    } = footprint
+    else {
+        unreachable!()
+    };
+    let edge = *center + Rot2::radians(*angle) * Vec2::new(half_size.x, 0.0);
+    assert!((footprint.coverage(edge) - 0.5).abs() < 1e-4);
+    assert_eq!(footprint.coverage(*center), 1.0);
+    assert_eq!(
+        footprint.coverage(edge + Vec2::splat(VOXEL_SIZE * 3.0)),
+        0.0
+    );
+}
@@ -0,0 +1,59 @@
+use bevy::prelude::*;
+use voxel_2d::contour::{extract_contours, extract_contours_in};
+use voxel_2d::marching_squares::SaddleResolution;
+use voxel_2d::voxel_map::VoxelMap;
+use voxel_2d::{ISO_LEVEL, VOXEL_SIZE};
+
+// 在空地图上填一块实心的矩形，max 不包含在内
+fn block(min: IVec2, max: IVec2) -> VoxelMap {
+    let mut map = VoxelMap::empty(Vec2::ZERO);
+    for y in min.y..max.y {
+        for x in min.x..max.x {
+            map.set_density(x, y, 1.0);
+        }
+    }
+    map
+}
+
+#[test]
+fn island_is_one_ccw_loop() {
+    let map = block(IVec2::new(2, 2), IVec2::new(5, 5));
+    let contours = extract_contours(&map, ISO_LEVEL, SaddleResolution::default());
+    assert_eq!(contours.len(), 1);
+    assert!(contours[0].closed);
+    assert!(contours[0].is_island());
+    assert!(contours[0].signed_area() > 0.0);
+}
+
+#[test]
+fn hole_is_a_cw_loop() {
+    let mut map = block(IVec2::new(2, 2), IVec2::new(9, 9));
+    map.set_density(5, 5, 0.0);
+    let contours = extract_contours(&map, ISO_LEVEL, SaddleResolution::default());
+    assert_eq!(contours.len(), 2);
+    assert_eq!(contours.iter().filter(|c| c.is_island()).count(), 1);
+    let hole = contours.iter().find(|c| c.is_hole()).unwrap();
+    assert!(hole.signed_area() < 0.0);
+    // 洞在岛屿里面
+    let center = map.grid_to_world(5, 5);
+    assert!(
+        hole.points
+            .iter()
+            .all(|p| p.distance(center) < VOXEL_SIZE * 1.5)
+    );
+}
+
+#[test]
+fn range_edge_gives_open_chains() {
+    let map = block(IVec2::new(2, 2), IVec2::new(9, 9));
+    let (min, max) = (IVec2::new(0, 0), IVec2::new(4, 10));
+    let contours = extract_contours_in(&map, min, max, ISO_LEVEL, SaddleResolution::default());
+    assert_eq!(contours.len(), 1);
+    let chain = &contours[0];
+    assert!(!chain.closed);
+    // 两端都落在范围的右边界上（格子 x = 4 的右边，也就是格点 x = 5）
+    let edge_x = map.grid_to_world(max.x + 1, 0).x;
+    for end in [chain.points[0], *chain.points.last().unwrap()] {
+        assert_eq!(end.x, edge_x);
+    }
+}
@@ -0,0 +1,78 @@
+use voxel_2d::history::EditHistory;
+use voxel_2d::material::Material;
+use voxel_2d::voxel_map::VoxelMap;
+
+// 一笔：把 (x, y) 改成给定的密度和材质
+fn edit(map: &mut VoxelMap, x: i32, y: i32, density: f32, material: Material) -> EditHistory {
+    let mut history = EditHistory::default();
+    map.begin_edit();
+    map.set_voxel(x, y, density, material);
+    history.push(map.end_edit().unwrap());
+    history
+}
+
+#[test]
+fn undo_and_redo_restore_density_and_material() {
+    let mut map = VoxelMap::new(16, 16);
+    let mut history = edit(&mut map, 3, 4, 0.25, Material::Gold);
+    assert!(history.can_undo());
+    assert!(!history.can_redo());
+
+    assert!(history.undo(&mut map));
+    assert_eq!(map.get_density(3, 4), 1.0);
+    assert_eq!(map.get_material(3, 4), Material::Dirt);
+    assert!(!history.undo(&mut map));
+
+    assert!(history.redo(&mut map));
+    assert_eq!(map.get_density(3, 4), 0.25);
+    assert_eq!(map.get_material(3, 4), Material::Gold);
+    assert!(!history.redo(&mut map));
+}
+
+#[test]
+fn new_edit_clears_redo() {
+    let mut map = VoxelMap::new(16, 16);
+    let mut history = edit(&mut map, 1, 1, 0.0, Material::Dirt);
+    history.undo(&mut map);
+    assert!(history.can_redo());
+
+    map.begin_edit();
+    map.set_density(2, 2, 0.5);
+    history.push(map.end_edit().unwrap());
+    assert!(!history.can_redo());
+    assert!(!history.redo(&mut map));
+    assert_eq!(map.get_density(1, 1), 1.0);
+}
+
+#[test]
+fn capacity_drops_oldest_steps() {
+    let mut map = VoxelMap::new(16, 16);
+    let mut history = EditHistory::new(2);
+    for x in 0..3 {
+        map.begin_edit();
+        map.set_density(x, 0, 0.0);
+        history.push(map.end_edit().unwrap());
+    }
+    assert!(history.undo(&mut map));
+    assert!(history.undo(&mut map));
+    assert!(!history.undo(&mut map));
+    // 第一步已经被挤掉，撤销不回来
+    assert_eq!(map.get_density(0, 0), 0.0);
+    assert_eq!(map.get_density(1, 0), 1.0);
+    assert_eq!(map.get_density(2, 0), 1.0);
+}
+
+#[test]
+fn unchanged_edit_is_none() {
+    let mut map = VoxelMap::new(16, 16);
+    assert!(map.end_edit().is_none()); // 没开始记录
+
+    map.begin_edit();
+    assert!(map.end_edit().is_none());
+
+    // 改了又改回去也算没变
+    map.begin_edit();
+    map.set_density(5, 5, 0.3);
+    map.set_density(5, 5, 1.0);
+    assert!(map.end_edit().is_none());
+}
@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+use voxel_2d::VOXEL_SIZE;
+use voxel_2d::brush::BrushSettings;
+use voxel_2d::image_io::ImageImport;
+use voxel_2d::voxel_map::VoxelMap;
+
+fn assert_same_densities(a: &VoxelMap, b: &VoxelMap, min: IVec2, max: IVec2) {
+    for y in min.y..max.y {
+        for x in min.x..max.x {
+            let (da, db) = (a.get_density(x, y), b.get_density(x, y));
+            assert!(
+                (da - db).abs() <= 0.5 / 255.0 + 1e-6,
+                "({x}, {y}): {da} vs {db}"
+            );
+        }
+    }
+}
+
+#[test]
+fn grayscale_image_round_trip() {
+    let mut map = VoxelMap::new(40, 30);
+    BrushSettings::default().stamp(&mut map, Vec2::new(-30.0, 10.0), 0.7, true);
+    let image = map.to_image();
+    assert_eq!(image.dimensions(), (40, 30));
+
+    // 导出再导入，原点和格子坐标都不变
+    let loaded = VoxelMap::from_image(&image, ImageImport::default());
+    assert_eq!(loaded.origin(), map.origin());
+    assert_same_densities(&map, &loaded, IVec2::splat(-2), IVec2::new(42, 32));
+}
+
+#[test]
+fn air_edges_keep_map_in_place() {
+    // 像横版地表一样：上面几行是天空，左右两边也挖空了
+    let mut map = VoxelMap::new(40, 30);
+    for y in 0..30 {
+        for x in 0..40 {
+            if y >= 20 || !(3..37).contains(&x) {
+                map.set_density(x, y, 0.0);
+            }
+        }
+    }
+    let image = map.to_image();
+    assert_eq!(image.dimensions(), (40, 30));
+
+    let loaded = VoxelMap::from_image(&image, ImageImport::default());
+    assert_eq!(loaded.origin(), map.origin());
+    assert_same_densities(&map, &loaded, IVec2::splat(-2), IVec2::new(42, 32));
+    assert_eq!(loaded.content_bounds(), map.content_bounds());
+}
+
+#[test]
+fn content_outside_the_map_is_included() {
+    let mut map = VoxelMap::new(40, 30);
+    map.set_density(-50, -50, 0.8);
+    let (min, max) = map.image_bounds().unwrap();
+    assert_eq!((min, max), (IVec2::new(-50, -50), IVec2::new(40, 30)));
+    let image = map.to_image();
+    assert_eq!(UVec2::from(image.dimensions()), (max - min).as_uvec2());
+
+    let half = VoxelMap::from_image(
+        &image,
+        ImageImport {
+            size: Some((max - min).as_uvec2() / 2),
+            invert: false,
+        },
+    );
+    assert_eq!(half.to_image().height(), image.height() / 2);
+    assert_eq!(half.origin(), -Vec2::new(45.0, 40.0) * VOXEL_SIZE / 2.0);
+}
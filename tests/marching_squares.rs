@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+use voxel_2d::ISO_LEVEL;
+use voxel_2d::marching_squares::{
+    CellEdge, SaddleResolution, case_index, march_cell, march_cell_edges,
+};
+
+const MODES: [SaddleResolution; 3] = [
+    SaddleResolution::Legacy,
+    SaddleResolution::CenterSample,
+    SaddleResolution::AsymptoticDecider,
+];
+
+// 单位正方形的四个角：左下、右下、右上、左上
+const CORNERS: [Vec2; 4] = [
+    Vec2::new(0.0, 0.0),
+    Vec2::new(1.0, 0.0),
+    Vec2::new(1.0, 1.0),
+    Vec2::new(0.0, 1.0),
+];
+
+// 鞍点：强连通、弱连通，以及两种判断方法意见不同的情况
+const STRONG_5: [f32; 4] = [1.0, 0.1, 1.0, 0.1];
+const WEAK_5: [f32; 4] = [0.6, 0.0, 0.6, 0.0];
+const DISAGREE_5: [f32; 4] = [0.52, 0.3, 1.0, 0.35]; // 平均 0.5425，渐近线判定 0.477
+const STRONG_10: [f32; 4] = [0.1, 1.0, 0.1, 1.0];
+const WEAK_10: [f32; 4] = [0.0, 0.6, 0.0, 0.6];
+const DISAGREE_10: [f32; 4] = [0.3, 1.0, 0.35, 0.52];
+
+#[test]
+fn saddle_connectivity_per_mode() {
+    // (角的密度, 情况, Legacy, CenterSample, AsymptoticDecider)
+    let table = [
+        (STRONG_5, 5, false, true, true),
+        (WEAK_5, 5, false, false, false),
+        (DISAGREE_5, 5, false, true, false),
+        (STRONG_10, 10, false, true, true),
+        (WEAK_10, 10, false, false, false),
+        (DISAGREE_10, 10, false, true, false),
+    ];
+    for (values, case, legacy, center, asymptotic) in table {
+        assert_eq!(case_index(values, ISO_LEVEL), case);
+        for (mode, expected) in MODES.into_iter().zip([legacy, center, asymptotic]) {
+            assert_eq!(
+                mode.connects(values, ISO_LEVEL),
+                expected,
+                "{values:?} {mode:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn saddle_edges_follow_connectivity() {
+    use CellEdge::{Bottom, Left, Right, Top};
+    let edges =
+        |values, mode| -> Vec<_> { march_cell_edges(values, ISO_LEVEL, mode).iter().collect() };
+
+    // 分开：两个实心角各自被切下来；连通：两个空气角各自被切下来
+    assert_eq!(
+        edges(STRONG_5, SaddleResolution::Legacy),
+        [(Bottom, Left), (Top, Right)]
+    );
+    assert_eq!(
+        edges(STRONG_5, SaddleResolution::AsymptoticDecider),
+        [(Bottom, Right), (Top, Left)]
+    );
+    assert_eq!(
+        edges(STRONG_10, SaddleResolution::Legacy),
+        [(Right, Bottom), (Left, Top)]
+    );
+    assert_eq!(
+        edges(STRONG_10, SaddleResolution::CenterSample),
+        [(Left, Bottom), (Right, Top)]
+    );
+}
+
+#[test]
+fn segments_keep_solid_on_the_left() {
+    let mut inputs: Vec<[f32; 4]> = (0..16)
+        .map(|case| std::array::from_fn(|bit| if case & (1 << bit) != 0 { 1.0 } else { 0.0 }))
+        .collect();
+    inputs.extend([
+        STRONG_5,
+        WEAK_5,
+        DISAGREE_5,
+        STRONG_10,
+        WEAK_10,
+        DISAGREE_10,
+    ]);
+
+    for values in inputs {
+        for mode in MODES {
+            for [a, b] in march_cell(CORNERS, values, ISO_LEVEL, mode).iter() {
+                let middle = (a + b) / 2.0;
+                let side = |corner: Vec2| (b - a).perp_dot(corner - a);
+                // 离线段最近的实心角在左边，最近的空气角在右边
+                for solid in [true, false] {
+                    let nearest = (0..4)
+                        .filter(|&i| (values[i] >= ISO_LEVEL) == solid)
+                        .map(|i| CORNERS[i])
+                        .min_by(|p, q| p.distance(middle).total_cmp(&q.distance(middle)))
+                        .unwrap();
+                    assert_eq!(side(nearest) > 0.0, solid, "{values:?} {mode:?} {a} -> {b}");
+                }
+            }
+        }
+    }
+}
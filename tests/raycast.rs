@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+use voxel_2d::VOXEL_SIZE;
+use voxel_2d::marching_squares::SaddleResolution;
+use voxel_2d::voxel_map::VoxelMap;
+
+// 左边是空气，x >= 10 是一堵实心墙，墙面在 x = 9.5 的位置
+fn wall_map() -> VoxelMap {
+    let mut map = VoxelMap::empty(Vec2::ZERO);
+    for y in 0..20 {
+        for x in 10..20 {
+            map.set_density(x, y, 1.0);
+        }
+    }
+    map
+}
+
+fn world(map: &VoxelMap, x: f32, y: f32) -> Vec2 {
+    map.origin() + Vec2::new(x, y) * VOXEL_SIZE
+}
+
+#[test]
+fn raycast_hits_wall_with_normal() {
+    let map = wall_map();
+    let saddle = SaddleResolution::default();
+    let origin = world(&map, 2.0, 10.3);
+    let hit = map.raycast(origin, Vec2::X, 1000.0, saddle).unwrap();
+    assert!(hit.point.distance(world(&map, 9.5, 10.3)) < 1e-3, "{hit:?}");
+    assert!((hit.distance - 7.5 * VOXEL_SIZE).abs() < 1e-3);
+    assert!(hit.normal.distance(Vec2::NEG_X) < 1e-3, "{:?}", hit.normal);
+
+    // 够不着
+    assert!(
+        map.raycast(origin, Vec2::X, 7.0 * VOXEL_SIZE, saddle)
+            .is_none()
+    );
+}
+
+#[test]
+fn raycast_misses_and_starts_inside() {
+    let map = wall_map();
+    let saddle = SaddleResolution::default();
+    let origin = world(&map, 2.0, 10.3);
+    assert!(
+        map.raycast(origin, Vec2::NEG_X, f32::INFINITY, saddle)
+            .is_none()
+    );
+    assert!(
+        map.raycast(origin, Vec2::Y, f32::INFINITY, saddle)
+            .is_none()
+    );
+
+    let inside = world(&map, 15.0, 10.0);
+    let hit = map.raycast(inside, Vec2::X, 100.0, saddle).unwrap();
+    assert_eq!(hit.distance, 0.0);
+    assert_eq!(hit.point, inside);
+}
+
+#[test]
+fn circle_cast_is_bounded() {
+    let map = wall_map();
+    let saddle = SaddleResolution::default();
+    let origin = world(&map, 2.0, 10.3);
+    let radius = VOXEL_SIZE;
+
+    // 无穷远也要能马上返回
+    assert!(
+        map.circle_cast(origin, Vec2::NEG_X, radius, f32::INFINITY, saddle)
+            .is_none()
+    );
+    let far = origin + Vec2::new(0.0, 1.0e6);
+    assert!(
+        map.circle_cast(far, Vec2::X, radius, f32::INFINITY, saddle)
+            .is_none()
+    );
+
+    let hit = map
+        .circle_cast(origin, Vec2::X, radius, f32::INFINITY, saddle)
+        .unwrap();
+    assert!(
+        (hit.distance - (7.5 * VOXEL_SIZE - radius)).abs() < 0.05,
+        "{hit:?}"
+    );
+    assert!(hit.normal.distance(Vec2::NEG_X) < 1e-2, "{:?}", hit.normal);
+}
+
+#[test]
+fn saddle_mode_matches_rendering() {
+    // 对角两个实心角：分开时中心是空气，连通时中心是实心
+    let mut map = VoxelMap::empty(Vec2::ZERO);
+    map.set_density(0, 0, 1.0);
+    map.set_density(1, 1, 1.0);
+    let center = world(&map, 0.5, 0.5);
+    assert!(!map.is_solid_at(center, SaddleResolution::Legacy));
+    assert!(map.is_solid_at(center, SaddleResolution::CenterSample));
+}
@@ -0,0 +1,113 @@
+use bevy::prelude::*;
+use voxel_2d::brush::BrushSettings;
+use voxel_2d::contour::{Contour, extract_contours};
+use voxel_2d::marching_squares::SaddleResolution;
+use voxel_2d::material::Material;
+use voxel_2d::save::{Quantization, SaveOptions};
+use voxel_2d::voxel_map::VoxelMap;
+use voxel_2d::{ISO_LEVEL, VOXEL_SIZE};
+
+// 一张挖过几个洞、填过不同材质的地图，密度不全是 0 和 1
+fn sample_map() -> VoxelMap {
+    let mut map = VoxelMap::new(40, 30);
+    let brush = BrushSettings::default();
+    brush.stamp(&mut map, Vec2::new(-30.0, 10.0), 0.7, true);
+    brush.stamp(&mut map, Vec2::new(25.0, -40.0), 2.0, true);
+    let gold = BrushSettings {
+        material: Material::Gold,
+        radius: 3.0,
+        ..default()
+    };
+    gold.stamp(&mut map, Vec2::new(25.0, -40.0), 0.9, false);
+    // 地图外面单独的一块，区块坐标是负数
+    map.set_voxel(-50, -50, 0.8, Material::Stone);
+    map
+}
+
+fn round_trip(map: &VoxelMap, options: SaveOptions) -> VoxelMap {
+    let mut bytes = Vec::new();
+    map.write_to(&mut bytes, options).unwrap();
+    VoxelMap::read_from(bytes.as_slice()).unwrap()
+}
+
+fn assert_same_shape(a: &[Contour], b: &[Contour], tolerance: f32) {
+    assert_eq!(a.len(), b.len());
+    for (a, b) in a.iter().zip(b) {
+        assert_eq!(a.closed, b.closed);
+        assert_eq!(a.points.len(), b.points.len());
+        for (p, q) in a.points.iter().zip(&b.points) {
+            assert!(p.distance(*q) <= tolerance, "{p} vs {q}");
+        }
+    }
+}
+
+#[test]
+fn lossless_round_trip_keeps_contours_identical() {
+    let map = sample_map();
+    for compress in [false, true] {
+        let options = SaveOptions {
+            quantization: Quantization::F32,
+            compress,
+            ..default()
+        };
+        let loaded = round_trip(&map, options);
+        assert_eq!(loaded.chunk_count(), map.chunk_count());
+        assert_eq!(
+            extract_contours(&loaded, ISO_LEVEL, SaddleResolution::default()),
+            extract_contours(&map, ISO_LEVEL, SaddleResolution::default())
+        );
+    }
+}
+
+#[test]
+fn quantised_round_trip_keeps_contour_topology() {
+    let map = sample_map();
+    let original = extract_contours(&map, ISO_LEVEL, SaddleResolution::default());
+    let loaded = round_trip(&map, SaveOptions::default());
+    assert_same_shape(
+        &extract_contours(&loaded, ISO_LEVEL, SaddleResolution::default()),
+        &original,
+        VOXEL_SIZE * 1e-3,
+    );
+}
+
+#[test]
+fn materials_survive_round_trip() {
+    let map = sample_map();
+    let loaded = round_trip(&map, SaveOptions::default());
+    for y in -60..40 {
+        for x in -60..50 {
+            if map.get_density(x, y) > 0.0 {
+                assert_eq!(loaded.get_material(x, y), map.get_material(x, y));
+            }
+        }
+    }
+
+    let without = round_trip(
+        &map,
+        SaveOptions {
+            materials: false,
+            ..default()
+        },
+    );
+    assert_eq!(without.get_material(-50, -50), Material::default());
+}
+
+#[test]
+fn save_and_load_through_a_file() {
+    let map = sample_map();
+    let path = std::env::temp_dir().join(format!("voxel_2d_{}.vx2d", std::process::id()));
+    map.save(&path).unwrap();
+    let loaded = VoxelMap::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_same_shape(
+        &extract_contours(&loaded, ISO_LEVEL, SaddleResolution::default()),
+        &extract_contours(&map, ISO_LEVEL, SaddleResolution::default()),
+        VOXEL_SIZE * 1e-3,
+    );
+}
+
+#[test]
+fn rejects_other_files() {
+    assert!(VoxelMap::read_from(&b"PNG\0 not a map"[..]).is_err());
+}
@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+use voxel_2d::contour::Contour;
+use voxel_2d::simplify::{SimplifyMethod, ramer_douglas_peucker, self_intersects, visvalingam};
+
+const METHODS: [SimplifyMethod; 2] = [
+    SimplifyMethod::RamerDouglasPeucker,
+    SimplifyMethod::Visvalingam,
+];
+
+fn contour(points: &[[f32; 2]], closed: bool) -> Contour {
+    Contour {
+        points: points.iter().map(|p| Vec2::from(*p)).collect(),
+        closed,
+    }
+}
+
+#[test]
+fn removes_collinear_points() {
+    // 正方形的每条边上都有多余的共线点
+    let square = contour(
+        &[
+            [0.0, 0.0],
+            [1.0, 0.0],
+            [2.0, 0.0],
+            [2.0, 1.0],
+            [2.0, 2.0],
+            [1.0, 2.0],
+            [0.0, 2.0],
+            [0.0, 1.0],
+        ],
+        true,
+    );
+    for method in METHODS {
+        let simplified = square.simplified(0.01, method);
+        assert_eq!(simplified.points.len(), 4, "{method:?}");
+        assert!(simplified.closed);
+    }
+}
+
+#[test]
+fn closed_loop_keeps_three_points() {
+    // 一个很小的环，容差比它本身还大
+    let tiny = contour(&[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]], true);
+    for method in METHODS {
+        let simplified = tiny.simplified(100.0, method);
+        assert!(simplified.points.len() >= 3, "{method:?}");
+    }
+    assert!(visvalingam(&tiny.points, true, 100.0).len() >= 3);
+}
+
+#[test]
+fn open_chain_keeps_endpoints() {
+    let chain = contour(
+        &[[0.0, 0.0], [1.0, 0.1], [2.0, -0.1], [3.0, 0.05], [4.0, 0.0]],
+        false,
+    );
+    for method in METHODS {
+        let simplified = chain.simplified(0.5, method);
+        assert_eq!(simplified.points, vec![Vec2::ZERO, Vec2::new(4.0, 0.0)]);
+        assert!(!simplified.closed);
+    }
+}
+
+#[test]
+fn falls_back_instead_of_self_intersecting() {
+    // 直接用 RDP 简化会让第一段穿过后面的线
+    let chain = contour(
+        &[[3.0, 8.0], [5.0, 7.0], [7.0, 0.0], [2.0, 5.0], [6.0, 2.0]],
+        false,
+    );
+    assert!(!self_intersects(&chain.points, false));
+    let raw = ramer_douglas_peucker(&chain.points, false, 2.0);
+    assert!(self_intersects(&raw, false));
+
+    let simplified = chain.simplified(2.0, SimplifyMethod::RamerDouglasPeucker);
+    assert!(!self_intersects(&simplified.points, false));
+    assert_eq!(simplified.points.first(), chain.points.first());
+    assert_eq!(simplified.points.last(), chain.points.last());
+}
@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+use voxel_2d::voxel_map::VoxelMap;
+use voxel_2d::{CHUNK_SIZE, VOXEL_SIZE};
+
+#[test]
+fn split_coord_handles_negative_coordinates() {
+    assert_eq!(
+        VoxelMap::split_coord(-1, -1),
+        (IVec2::new(-1, -1), IVec2::splat(CHUNK_SIZE - 1))
+    );
+    assert_eq!(
+        VoxelMap::split_coord(-CHUNK_SIZE, 0),
+        (IVec2::new(-1, 0), IVec2::ZERO)
+    );
+    assert_eq!(
+        VoxelMap::split_coord(CHUNK_SIZE - 1, 0),
+        (IVec2::ZERO, IVec2::new(CHUNK_SIZE - 1, 0))
+    );
+    assert_eq!(
+        VoxelMap::split_coord(CHUNK_SIZE, 0),
+        (IVec2::new(1, 0), IVec2::ZERO)
+    );
+}
+
+#[test]
+fn densities_across_chunk_borders() {
+    let mut map = VoxelMap::empty(Vec2::ZERO);
+    map.set_density(CHUNK_SIZE - 1, 0, 0.25);
+    map.set_density(CHUNK_SIZE, 0, 0.75);
+    map.set_density(-1, -1, 1.0);
+    assert_eq!(map.get_density(CHUNK_SIZE - 1, 0), 0.25);
+    assert_eq!(map.get_density(CHUNK_SIZE, 0), 0.75);
+    assert_eq!(map.get_density(-1, -1), 1.0);
+    assert_eq!(map.get_density(0, 0), 0.0);
+    assert_eq!(map.chunk_count(), 3);
+
+    // 超出 0..1 的值被截断
+    map.set_density(2, 2, 3.0);
+    assert_eq!(map.get_density(2, 2), 1.0);
+}
+
+#[test]
+fn chunks_are_created_lazily_and_dropped_when_empty() {
+    let mut map = VoxelMap::empty(Vec2::ZERO);
+    assert_eq!(map.chunk_count(), 0);
+
+    // 往没有区块的地方写空气不会创建区块
+    map.set_density(5, 5, 0.0);
+    assert_eq!(map.chunk_count(), 0);
+
+    map.set_density(5, 5, 0.5);
+    map.set_density(6, 5, 0.5);
+    assert_eq!(map.chunk_count(), 1);
+
+    map.set_density(5, 5, 0.0);
+    assert_eq!(map.chunk_count(), 1);
+    map.set_density(6, 5, 0.0);
+    assert_eq!(map.chunk_count(), 0);
+    assert!(map.bounds().is_none());
+}
+
+#[test]
+fn world_to_grid_floors_negative_positions() {
+    let map = VoxelMap::empty(Vec2::ZERO);
+    assert_eq!(map.world_to_grid(Vec2::new(0.5, 0.5)), (0, 0));
+    assert_eq!(map.world_to_grid(Vec2::new(-0.5, -0.5)), (-1, -1));
+    assert_eq!(
+        map.world_to_grid(Vec2::new(-VOXEL_SIZE, -VOXEL_SIZE - 0.1)),
+        (-1, -2)
+    );
+
+    // 居中的地图：世界原点落在中间的格子上
+    let centred = VoxelMap::new(10, 8);
+    assert_eq!(centred.world_to_grid(Vec2::ZERO), (5, 4));
+    assert_eq!(
+        centred.world_to_grid(centred.grid_to_world(-3, -7)),
+        (-3, -7)
+    );
+}
@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use bevy::prelude::*;
+use voxel_2d::brush::{BrushMode, BrushSettings};
+use voxel_2d::history::EditHistory;
+use voxel_2d::material::Material;
+use voxel_2d::voxel_map::VoxelMap;
+use voxel_2d::worldgen::{
+    CellularCaves, GenRegion, Generator, Neighbourhood, OreVeins, WorldGenConfig, WorldGenPipeline,
+    WorldGenStep, step_seed,
+};
+
+fn densities(map: &VoxelMap, config: &WorldGenConfig) -> Vec<(f32, Material)> {
+    let mut out = Vec::new();
+    for y in 0..config.height as i32 {
+        for x in 0..config.width as i32 {
+            out.push((map.get_density(x, y), map.get_material(x, y)));
+        }
+    }
+    out
+}
+
+#[test]
+fn same_seed_same_map() {
+    for generator in [Generator::Noise, Generator::Cellular] {
+        let config = WorldGenConfig {
+            generator,
+            ..default()
+        };
+        let a = WorldGenPipeline::preset(generator).generate(&config);
+        let b = WorldGenPipeline::preset(generator).generate(&config);
+        assert_eq!(densities(&a, &config), densities(&b, &config));
+
+        let other = WorldGenConfig {
+            seed: config.seed + 1,
+            ..config.clone()
+        };
+        let c = WorldGenPipeline::preset(generator).generate(&other);
+        assert_ne!(densities(&a, &config), densities(&c, &other));
+    }
+}
+
+#[test]
+fn cellular_caves_have_no_chunk_seams() {
+    // 按区块生成的结果和整张一次模拟的结果一致
+    let config = WorldGenConfig {
+        width: 70,
+        height: 50,
+        ..default()
+    };
+    let caves = CellularCaves::default();
+    let map = WorldGenPipeline::new().with_step(caves).generate(&config);
+    // 流水线里的第 0 步
+    let whole = caves.simulate(
+        step_seed(config.seed, 0),
+        IVec2::ZERO,
+        IVec2::new(config.width as i32, config.height as i32),
+    );
+    for (i, expected) in whole.iter().enumerate() {
+        let (x, y) = ((i % config.width) as i32, (i / config.width) as i32);
+        assert_eq!(map.get_density(x, y), *expected, "({x}, {y})");
+    }
+}
+
+#[test]
+fn stacked_cellular_steps_differ() {
+    // 同样的两步，各自的随机填充不一样
+    let config = WorldGenConfig::default();
+    let caves = CellularCaves::default();
+    let size = IVec2::new(config.width as i32, config.height as i32);
+    let first = caves.simulate(step_seed(config.seed, 0), IVec2::ZERO, size);
+    let second = caves.simulate(step_seed(config.seed, 1), IVec2::ZERO, size);
+    assert_ne!(first, second);
+
+    let von_neumann = CellularCaves {
+        neighbourhood: Neighbourhood::VonNeumann,
+        birth: 3,
+        survive: 2,
+        ..caves
+    };
+    assert_ne!(
+        von_neumann.simulate(step_seed(config.seed, 0), IVec2::ZERO, size),
+        first
+    );
+}
+
+// 把整个区块填成固定的密度
+struct Fill(f32);
+
+impl WorldGenStep for Fill {
+    fn apply(&self, region: &mut GenRegion) {
+        for cell in region.cells() {
+            region.set_density(cell.x, cell.y, self.0);
+        }
+    }
+}
+
+// 读出上一步写的密度，翻倍后写回去
+struct Double;
+
+impl WorldGenStep for Double {
+    fn apply(&self, region: &mut GenRegion) {
+        for cell in region.cells() {
+            let density = region.density(cell.x, cell.y);
+            region.set_density(cell.x, cell.y, density * 2.0);
+        }
+    }
+}
+
+#[test]
+fn steps_run_in_order() {
+    let config = WorldGenConfig::default();
+    let map = WorldGenPipeline::new()
+        .with_step(Fill(0.3))
+        .with_step(Double)
+        .generate(&config);
+    let reversed = WorldGenPipeline::new()
+        .with_step(Double)
+        .with_step(Fill(0.3))
+        .generate(&config);
+    for (density, _) in densities(&map, &config) {
+        assert!((density - 0.6).abs() < 1e-6);
+    }
+    for (density, _) in densities(&reversed, &config) {
+        assert!((density - 0.3).abs() < 1e-6);
+    }
+}
+
+// 记下每个区块的随机数序列的第一个值
+struct Record(Arc<Mutex<HashMap<IVec2, u64>>>);
+
+impl WorldGenStep for Record {
+    fn apply(&self, region: &mut GenRegion) {
+        use rand::Rng;
+        let value = region.rng.random();
+        self.0.lock().unwrap().insert(region.chunk, value);
+    }
+}
+
+fn record(seed: u64, min: IVec2, max: IVec2) -> HashMap<IVec2, u64> {
+    let values = Arc::new(Mutex::new(HashMap::new()));
+    let pipeline = WorldGenPipeline::new().with_step(Record(values.clone()));
+    pipeline.run(&mut VoxelMap::empty(Vec2::ZERO), seed, min, max);
+    values.lock().unwrap().clone()
+}
+
+#[test]
+fn chunk_rng_depends_only_on_its_own_chunk() {
+    // 两次生成的范围不同，共有的区块拿到的随机数一样
+    let a = record(7, IVec2::ZERO, IVec2::new(48, 32));
+    let b = record(7, IVec2::new(-32, 16), IVec2::new(32, 64));
+    let shared: Vec<_> = a.keys().filter(|k| b.contains_key(*k)).collect();
+    assert!(!shared.is_empty());
+    for chunk in shared {
+        assert_eq!(a[chunk], b[chunk], "{chunk}");
+    }
+
+    // 不同区块之间互不相同
+    let mut values: Vec<_> = a.values().collect();
+    values.sort();
+    values.dedup();
+    assert_eq!(values.len(), a.len());
+
+    // 种子变了，随机数也变
+    let c = record(8, IVec2::ZERO, IVec2::new(48, 32));
+    assert!(a.iter().all(|(chunk, value)| c[chunk] != *value));
+}
+
+#[test]
+fn ore_veins_replace_only_solid_rock() {
+    let config = WorldGenConfig::default();
+    let caves = || WorldGenPipeline::new().with_step(CellularCaves::default());
+    let mut pipeline = caves();
+    for ore in OreVeins::defaults() {
+        pipeline.push(ore);
+    }
+    let plain = densities(&caves().generate(&config), &config);
+    let map = pipeline.generate(&config);
+    let ores = densities(&map, &config);
+
+    // 矿脉只改材质，不改密度，也不会出现在空气里
+    for ((density, material), (before, _)) in ores.iter().zip(&plain) {
+        assert_eq!(density, before);
+        if matches!(material, Material::Coal | Material::Iron | Material::Gold) {
+            assert!(*density >= 0.5);
+        }
+    }
+    assert!(map.material_total(Material::Coal) > 0.0);
+    assert!(map.material_total(Material::Iron) > 0.0);
+}
+
+#[test]
+fn mining_is_counted_per_material() {
+    let mut map = VoxelMap::new(16, 16);
+    map.set_material(3, 3, Material::Gold);
+    let before = map.material_total(Material::Gold);
+    map.dig_density(3, 3, 0.4);
+    map.dig_density(4, 4, 10.0);
+
+    let mined = map.mined(Material::Gold);
+    assert!(mined > 0.0);
+    assert!((map.material_total(Material::Gold) - (before - mined)).abs() < 1e-5);
+    assert_eq!(map.mined(Material::Dirt), 1.0);
+    assert_eq!(map.mined(Material::Stone), 0.0);
+}
+
+#[test]
+fn mined_follows_undo_and_ignores_smoothing() {
+    let mut map = VoxelMap::new(16, 16);
+    let mut history = EditHistory::default();
+    map.begin_edit();
+    map.dig_density(5, 5, 0.5);
+    history.push(map.end_edit().unwrap());
+    assert_eq!(map.mined(Material::Dirt), 0.5);
+
+    assert!(history.undo(&mut map));
+    assert_eq!(map.get_density(5, 5), 1.0);
+    assert_eq!(map.mined(Material::Dirt), 0.0);
+    assert!(history.redo(&mut map));
+    assert_eq!(map.mined(Material::Dirt), 0.5);
+
+    // 平滑笔刷降低密度，但不是挖矿
+    let smooth = BrushSettings {
+        mode: BrushMode::Smooth,
+        ..default()
+    };
+    let center = map.grid_to_world(5, 5);
+    smooth.filter(&mut map, center, 1.0, 0.0);
+    assert!(map.get_density(6, 5) < 1.0);
+    assert_eq!(map.mined(Material::Dirt), 0.5);
+}